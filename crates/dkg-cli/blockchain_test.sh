@@ -46,7 +46,7 @@ cargo run --bin dkg-cli -- allow \
  
 # helper to run the DKG command
 run() {
-    yes | cargo run --bin dkg-cli -- run -n $NODE_URL -p $(private_key $1) -c $ADDR -o $2
+    cargo run --bin dkg-cli -- run -n $NODE_URL -p $(private_key $1) -c $ADDR -o $2 --yes
 }
 
 # Each participant launches the job
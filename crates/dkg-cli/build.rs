@@ -1,28 +1,45 @@
 use ethers::{contract::Abigen, utils::Solc};
 use std::{fs::File, io::Write};
 
-const PATH: &str = "../../solidity/contracts/DKG.sol";
+const CONTRACTS: [(&str, &str, &str, &str); 2] = [
+    (
+        "../../solidity/contracts/DKG.sol",
+        "DKG",
+        "dkg.bin",
+        "./src/dkg_contract.rs",
+    ),
+    (
+        "../../solidity/contracts/Coordinator.sol",
+        "Coordinator",
+        "coordinator.bin",
+        "./src/coordinator_contract.rs",
+    ),
+];
+
 // Generates the bindings under `src/`
 fn main() {
-    // Only re-run the builder script if the contract changes
-    println!("cargo:rerun-if-changed={}", PATH);
+    for (path, name, bin_path, bindings_path) in CONTRACTS.iter() {
+        // Only re-run the builder script if the contract changes
+        println!("cargo:rerun-if-changed={}", path);
 
-    // compile the DKG contract (requires solc on the builder's system)
-    let contracts = Solc::new(PATH).build_raw().expect("could not compile");
-    let contract = contracts.get("DKG").expect("contract not found");
+        // compile the contract (requires solc on the builder's system)
+        let contracts = Solc::new(path).build_raw().expect("could not compile");
+        let contract = contracts.get(*name).expect("contract not found");
 
-    let abi = contract.abi.clone();
+        let abi = contract.abi.clone();
 
-    let mut f = File::create("dkg.bin").expect("could not create DKG bytecode file");
-    f.write_all(contract.bin.as_bytes())
-        .expect("could not write DKG bytecode to the file");
+        let mut f = File::create(bin_path)
+            .unwrap_or_else(|_| panic!("could not create {} bytecode file", name));
+        f.write_all(contract.bin.as_bytes())
+            .unwrap_or_else(|_| panic!("could not write {} bytecode to the file", name));
 
-    // generate type-safe bindings to it
-    let bindings = Abigen::new("DKG", abi)
-        .expect("could not instantiate Abigen")
-        .generate()
-        .expect("could not generate bindings");
-    bindings
-        .write_to_file("./src/dkg_contract.rs")
-        .expect("could not write bindings to file");
+        // generate type-safe bindings to it
+        let bindings = Abigen::new(name, abi)
+            .expect("could not instantiate Abigen")
+            .generate()
+            .expect("could not generate bindings");
+        bindings
+            .write_to_file(bindings_path)
+            .expect("could not write bindings to file");
+    }
 }
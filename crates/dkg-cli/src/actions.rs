@@ -1,9 +1,19 @@
 use crate::{
+    board::{BoardContract, ContractKind, DKGBoard},
     dkg_contract::{DKG as DKGContract, DKG_ABI},
+    gas::GasReport,
+    hooks::Hooks,
     opts::*,
+    progress::{ProgressEvent, ProgressSink, StdoutProgress},
+    roster::{Roster, SignedRoster},
 };
 use rand::RngCore;
-use std::{fs::File, io::Write};
+use std::{
+    collections::BTreeSet,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+};
 
 use dkg_core::{
     primitives::{joint_feldman::*, *},
@@ -16,23 +26,35 @@ use rustc_hex::{FromHex, ToHex};
 use std::convert::TryFrom;
 
 use threshold_bls::poly::Idx;
-use threshold_bls::{group::Curve, sig::Scheme};
+use threshold_bls::{
+    group::{Curve, Element},
+    sig::Scheme,
+};
 
 #[derive(serde::Serialize, Debug)]
 struct CeloKeypairJson {
     address: Address,
     #[serde(rename = "privateKey")]
     private_key: String,
+    #[serde(rename = "blsPrivateKey")]
+    bls_private_key: String,
+    #[serde(rename = "blsPublicKey")]
+    bls_public_key: String,
 }
 
-pub fn keygen<R>(opts: KeygenOpts, rng: &mut R) -> Result<()>
+pub fn keygen<S, R>(opts: KeygenOpts, rng: &mut R) -> Result<()>
 where
+    S: Scheme,
     R: RngCore,
 {
     let wallet = Wallet::new(rng);
+    // the BLS key is what a coordinator gets initialized with
+    let (bls_private_key, bls_public_key) = S::keypair(rng);
     let output = CeloKeypairJson {
         private_key: hex::encode(bincode::serialize(wallet.private_key())?),
         address: wallet.address(),
+        bls_private_key: hex::encode(bincode::serialize(&bls_private_key)?),
+        bls_public_key: hex::encode(bincode::serialize(&bls_public_key)?),
     };
 
     if let Some(path) = opts.path {
@@ -82,7 +104,15 @@ pub async fn allow(opts: AllowlistOpts) -> Result<()> {
     }
 
     // Await them all
-    futures::future::join_all(tx_futs).await;
+    let receipts = futures::future::join_all(tx_futs).await;
+
+    if opts.report_gas || opts.gas_report_path.is_some() {
+        let mut report = GasReport::default();
+        for receipt in receipts {
+            report.record(contract.client(), "allow", &receipt?).await?;
+        }
+        write_gas_report(&report, opts.gas_report_path)?;
+    }
 
     Ok(())
 }
@@ -95,7 +125,15 @@ pub async fn start(opts: StartOpts) -> Result<()> {
 
     // Submit the tx and wait for the confirmation
     let tx_hash = contract.start().send().await?;
-    let _tx_receipt = contract.client().pending_transaction(tx_hash).await?;
+    let tx_receipt = contract.client().pending_transaction(tx_hash).await?;
+
+    if opts.report_gas || opts.gas_report_path.is_some() {
+        let mut report = GasReport::default();
+        report
+            .record(contract.client(), "start", &tx_receipt)
+            .await?;
+        write_gas_report(&report, opts.gas_report_path)?;
+    }
 
     Ok(())
 }
@@ -106,22 +144,66 @@ where
     // We need to bind the Curve's Point and Scalars to the Scheme
     S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>,
     R: RngCore,
+{
+    run_with_progress::<S, C, R, _>(opts, rng, &mut StdoutProgress).await
+}
+
+/// Runs the DKG like `run`, reporting its progress to `progress` instead of stdout
+pub async fn run_with_progress<S, C, R, P>(
+    opts: DKGConfig,
+    rng: &mut R,
+    progress: &mut P,
+) -> Result<()>
+where
+    C: Curve,
+    // We need to bind the Curve's Point and Scalars to the Scheme
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>,
+    R: RngCore,
+    P: ProgressSink,
 {
     let provider = Provider::<Http>::try_from(opts.node_url.as_str())?;
     let client = opts.private_key.parse::<Wallet>()?.connect(provider);
-    let mut dkg = DKGContract::new(opts.contract_address, client);
-
-    // 1. Generate the keys
-    let (private_key, public_key) = S::keypair(rng);
+    let our_address = client.address();
+    let mut dkg = DKGBoard::new(opts.contract_kind, opts.contract_address, client);
+    if opts.report_gas || opts.gas_report_path.is_some() {
+        dkg.gas_report = Some(GasReport::default());
+    }
+    let hooks = Hooks {
+        on_phase: opts.on_phase_hook.clone(),
+        on_complete: opts.on_complete_hook.clone(),
+    };
 
-    // 2. Register
-    println!("Registering...");
+    // 1. Generate the keys, unless we were given the one we registered with
+    let (private_key, public_key) = match opts.bls_private_key {
+        Some(ref private_key) => {
+            let private_key: S::Private = bincode::deserialize(&hex::decode(private_key)?)?;
+            let mut public_key = S::Public::one();
+            public_key.mul(&private_key);
+            (private_key, public_key)
+        }
+        None if opts.contract_kind == ContractKind::Coordinator => {
+            return Err(anyhow::anyhow!(
+                "The coordinator already holds the BLS public key you registered with, pass its private key with --bls-private-key."
+            ));
+        }
+        None => S::keypair(rng),
+    };
     let public_key_serialized = bincode::serialize(&public_key)?;
-    let pending_tx = dkg.register(public_key_serialized).send().await?;
-    let _tx_receipt = dkg.pending_transaction(pending_tx).await?;
+
+    // 2. Register (a coordinator is initialized with the participants' keys instead)
+    if let BoardContract::DKG(ref contract) = dkg.board_contract {
+        if contract.keys(our_address).call().await? == public_key_serialized {
+            progress.emit(ProgressEvent::AlreadyRegistered);
+        } else {
+            progress.emit(ProgressEvent::Registering);
+            let call = contract.register(public_key_serialized.clone());
+            dkg.send("register", None, call).await?;
+            dkg.flush_progress(progress);
+        }
+    }
 
     // Wait for Phase 1
-    wait_for_phase(&dkg, 1).await?;
+    wait_for_phase(&dkg, 1, progress, &hooks).await?;
 
     // Get the group info
     let group = dkg.get_bls_keys().call().await?;
@@ -137,7 +219,34 @@ where
         println!("{:?} -> {}", address, key)
     }
 
-    if !clt::confirm(
+    if let Some(ref path) = opts.roster_path {
+        let signer = check_roster(path, None, &participants, &group.1)?;
+        println!(
+            "The registered keys match the roster signed by {:?}.",
+            signer
+        );
+    }
+
+    if dkg.kind() == ContractKind::Coordinator {
+        let our_key = participants
+            .iter()
+            .position(|participant| *participant == our_address)
+            .map(|i| &group.1[i]);
+        if our_key != Some(&public_key_serialized) {
+            return Err(anyhow::anyhow!(
+                "The coordinator was not initialized with our address and BLS public key."
+            ));
+        }
+    }
+
+    if let Some(ref path) = opts.expect_participants {
+        check_expected_participants(path, &participants, &group.1)?;
+        println!("The registered participants are the expected ones.");
+    }
+
+    if opts.yes {
+        println!("Accepting the group (--yes).");
+    } else if !clt::confirm(
         "\nDoes the above group look good to you?",
         false,
         "\n",
@@ -146,76 +255,330 @@ where
         return Err(anyhow::anyhow!("User rejected group choice."));
     }
 
-    let nodes = group
-        .1
-        .into_iter()
-        .filter(|pubkey| !pubkey.is_empty()) // skip users that did not register
-        .enumerate()
-        .map(|(i, pubkey)| {
-            let pubkey: C::Point = bincode::deserialize(&pubkey)?;
-            Ok(Node::<C>::new(i as Idx, pubkey))
-        })
-        .collect::<Result<_>>()?;
-
-    let group = Group {
-        threshold: group.0.as_u64() as usize,
-        nodes,
+    let group = parse_group::<C>(group)?;
+
+    // Instantiate the DKG with the group info, or pick up the one we saved before being restarted
+    let phase0 = match opts.state_path {
+        Some(ref path) if Path::new(path).exists() => {
+            let phase0: DKG<C> = bincode::deserialize_from(File::open(path)?)?;
+            if phase0.info.public_key != public_key {
+                return Err(anyhow::anyhow!(
+                    "The DKG state at {} was created with another BLS key.",
+                    path
+                ));
+            }
+            progress.emit(ProgressEvent::ResumingFromState(path.clone()));
+            phase0
+        }
+        Some(ref path) => {
+            let phase0 = DKG::new(private_key, group)?;
+            // the state holds our secret, keep it from other users
+            bincode::serialize_into(create_private_file(path)?, &phase0)?;
+            phase0
+        }
+        None => DKG::new(private_key, group)?,
     };
 
-    // Instantiate the DKG with the group info
-    println!("Calculating and broadcasting our shares...");
-    let phase0 = DKG::new(private_key, group)?;
-
     // Run Phase 1 and publish to the chain
     let phase1 = phase0.run(&mut dkg, rng).await?;
+    dkg.flush_progress(progress);
 
     // Wait for Phase 2
-    wait_for_phase(&dkg, 2).await?;
+    wait_for_phase(&dkg, 2, progress, &hooks).await?;
 
     // Get the shares
-    let shares = dkg.get_shares().call().await?;
-    println!("Got {} shares...", shares.len());
-    let shares = parse_bundle(&shares)?;
-    println!("Parsed {} shares. Running Phase 2", shares.len());
+    let shares = parse_bundle(&dkg.get_shares().call().await?)?;
+    progress.emit(ProgressEvent::SharesReceived(shares.len()));
 
     let phase2 = phase1.run(&mut dkg, &shares).await?;
+    dkg.flush_progress(progress);
 
-    // Get the responses
-    let responses = dkg.get_responses().call().await?;
-    println!("Got {} responses...", responses.len());
-    let responses = parse_bundle(&responses)?;
-    println!("Parsed the responses. Getting result.");
+    // Get the responses, which only carry complaints
+    let responses: Vec<BundledResponses> = parse_bundle(&dkg.get_responses().call().await?)?;
+    let complaints = responses.iter().map(|bundle| bundle.responses.len()).sum();
+    progress.emit(ProgressEvent::Complaints(complaints));
 
     // Run Phase 2
-    let result = match phase2.run(&mut dkg, &responses).await? {
+    let phase2_result = phase2.run(&mut dkg, &responses).await?;
+    dkg.flush_progress(progress);
+    let result = match phase2_result {
         Phase2Result::Output(out) => Ok(out),
         // Run Phase 3 if Phase 2 errored
         Phase2Result::GoToPhase3(phase3) => {
-            println!("There were complaints. Running Phase 3.");
-            wait_for_phase(&dkg, 3).await?;
+            wait_for_phase(&dkg, 3, progress, &hooks).await?;
 
-            let justifications = dkg.get_justifications().call().await?;
-            let justifications = parse_bundle(&justifications)?;
+            let justifications = parse_bundle(&dkg.get_justifications().call().await?)?;
+            progress.emit(ProgressEvent::JustificationsReceived(justifications.len()));
 
-            phase3.run(&mut dkg, &justifications).await
+            let result = phase3.run(&mut dkg, &justifications).await;
+            dkg.flush_progress(progress);
+            result
         }
     };
 
+    if let Some(ref report) = dkg.gas_report {
+        write_gas_report(report, opts.gas_report_path)?;
+    }
+
     match result {
         Ok(output) => {
-            println!("Success. Your share and threshold pubkey are ready.");
-            if let Some(path) = opts.output_path {
+            let public_key = hex::encode(&bincode::serialize(&output.public.public_key())?);
+            progress.emit(ProgressEvent::Finished { public_key });
+            if let Some(ref path) = opts.output_path {
                 let file = File::create(path)?;
                 write_output(&file, &output)?;
             } else {
                 write_output(std::io::stdout(), &output)?;
             }
+            hooks.completed(opts.output_path.as_deref(), dkg.contract().address());
             Ok(())
         }
         Err(err) => Err(anyhow::anyhow!("DKG error: {}", err)),
     }
 }
 
+pub async fn recover<C: Curve>(opts: RecoverOpts) -> Result<()> {
+    let provider = Provider::<Http>::try_from(opts.node_url.as_str())?;
+    let client = opts.private_key.parse::<Wallet>()?.connect(provider);
+    let dkg = DKGBoard::new(opts.contract_kind, opts.contract_address, client);
+
+    // `inPhase` reverts once the DKG has ended
+    if let Ok(phase) = dkg.in_phase().call().await {
+        return Err(anyhow::anyhow!(
+            "The DKG is in phase {}, recover your share once it has ended.",
+            phase
+        ));
+    }
+
+    let private_key: C::Scalar = bincode::deserialize(&hex::decode(&opts.bls_private_key)?)?;
+    let group = parse_group::<C>(dkg.get_bls_keys().call().await?)?;
+
+    let shares = parse_bundle(&dkg.get_shares().call().await?)?;
+    let responses = parse_bundle(&dkg.get_responses().call().await?)?;
+    let justifications = parse_bundle(&dkg.get_justifications().call().await?)?;
+    println!(
+        "Got {} shares, {} responses and {} justifications. Recovering our share.",
+        shares.len(),
+        responses.len(),
+        justifications.len()
+    );
+
+    let output = recover_output(&private_key, &group, &shares, &responses, &justifications)?;
+
+    println!("Success. Your share and threshold pubkey are ready.");
+    if let Some(path) = opts.output_path {
+        let file = File::create(path)?;
+        write_output(&file, &output)?;
+    } else {
+        write_output(std::io::stdout(), &output)?;
+    }
+    Ok(())
+}
+
+pub fn roster_export(opts: RosterExportOpts) -> Result<()> {
+    let wallet = opts.private_key.parse::<Wallet>()?;
+
+    let mut roster = Roster::default();
+    for participant in &opts.participant {
+        let mut parts = participant.splitn(2, '=');
+        let (address, key) = match (parts.next(), parts.next()) {
+            (Some(address), Some(key)) => (address, key),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Expected `address=bls_public_key`, got `{}`.",
+                    participant
+                ))
+            }
+        };
+        let address = address.trim_start_matches("0x").parse::<Address>()?;
+        // re-encoded so that the roster is canonical whatever the case it was given in
+        let key = hex::encode(hex::decode(key.trim_start_matches("0x"))?);
+        if roster.participants.insert(address, key).is_some() {
+            return Err(anyhow::anyhow!("{:?} is listed twice.", address));
+        }
+    }
+
+    let signed = roster.sign(&wallet)?;
+
+    if let Some(path) = opts.path {
+        let f = File::create(path)?;
+        serde_json::to_writer_pretty(&f, &signed)?;
+    } else {
+        serde_json::to_writer_pretty(std::io::stdout(), &signed)?;
+    }
+
+    Ok(())
+}
+
+pub async fn roster_import(opts: RosterImportOpts) -> Result<()> {
+    let provider = Provider::<Http>::try_from(opts.node_url.as_str())?;
+    let client = opts.private_key.parse::<Wallet>()?.connect(provider);
+    let dkg = DKGBoard::new(opts.contract_kind, opts.contract_address, client);
+
+    let (_, keys) = dkg.get_bls_keys().call().await?;
+    let participants = dkg.get_participants().call().await?;
+
+    let signer = check_roster(&opts.roster_path, opts.signer, &participants, &keys)?;
+    println!(
+        "The roster signed by {:?} matches the {} registered keys.",
+        signer,
+        keys.iter().filter(|key| !key.is_empty()).count()
+    );
+
+    Ok(())
+}
+
+/// Reads the roster at `path`, verifies its signature and checks the registered keys
+/// against it. Returns the signer of the roster.
+fn check_roster(
+    path: &str,
+    expected_signer: Option<Address>,
+    participants: &[Address],
+    keys: &[Vec<u8>],
+) -> Result<Address> {
+    let signed: SignedRoster = serde_json::from_reader(File::open(path)?)?;
+
+    if let Some(expected_signer) = expected_signer {
+        if signed.signer != expected_signer {
+            return Err(anyhow::anyhow!(
+                "The roster at {} was exported by {:?}, not {:?}.",
+                path,
+                signed.signer,
+                expected_signer
+            ));
+        }
+    }
+
+    signed.verify()?.check(participants, keys)?;
+
+    Ok(signed.signer)
+}
+
+/// Compares the participants who registered a key with the addresses listed in the
+/// file at `path`, one per line
+fn check_expected_participants(
+    path: &str,
+    participants: &[Address],
+    keys: &[Vec<u8>],
+) -> Result<()> {
+    let expected = std::fs::read_to_string(path)?
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| Ok(line.trim_start_matches("0x").parse::<Address>()?))
+        .collect::<Result<BTreeSet<_>>>()?;
+
+    let registered = participants
+        .iter()
+        .zip(keys)
+        .filter(|(_, key)| !key.is_empty())
+        .map(|(address, _)| *address)
+        .collect::<BTreeSet<_>>();
+
+    let missing = expected.difference(&registered).collect::<Vec<_>>();
+    let unexpected = registered.difference(&expected).collect::<Vec<_>>();
+
+    if !missing.is_empty() || !unexpected.is_empty() {
+        return Err(anyhow::anyhow!(
+            "The registered participants differ from the expected ones. Missing: {:?}, unexpected: {:?}.",
+            missing,
+            unexpected
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn observe<C: Curve>(opts: ObserveOpts) -> Result<()> {
+    let provider = Provider::<Http>::try_from(opts.node_url.as_str())?;
+    let client = opts.private_key.parse::<Wallet>()?.connect(provider);
+    let dkg = DKGBoard::new(opts.contract_kind, opts.contract_address, client);
+    let progress = &mut StdoutProgress;
+
+    // follow the phases until `inPhase` reverts, which it does once the DKG has ended
+    let mut phase = None;
+    while let Ok(current) = dkg.in_phase().call().await {
+        let current = current.as_u64();
+        if phase != Some(current) {
+            if current == 0 {
+                println!("Waiting for the DKG to start");
+            } else {
+                progress.emit(ProgressEvent::PhaseEntered(current));
+            }
+            phase = Some(current);
+        } else {
+            progress.emit(ProgressEvent::StillWaiting);
+        }
+        // 6s for 1 Celo block
+        tokio::time::delay_for(std::time::Duration::from_millis(6000)).await;
+    }
+    println!("\nThe DKG has ended.");
+
+    let (threshold, keys) = dkg.get_bls_keys().call().await?;
+    let participants = dkg.get_participants().call().await?;
+    // the group only holds the participants who registered, in order
+    let registered = participants
+        .iter()
+        .zip(&keys)
+        .filter(|(_, key)| !key.is_empty())
+        .map(|(address, _)| *address)
+        .collect::<Vec<_>>();
+    let group = parse_group::<C>((threshold, keys))?;
+
+    let shares: Vec<BundledShares<C>> = parse_bundle(&dkg.get_shares().call().await?)?;
+    let responses: Vec<BundledResponses> = parse_bundle(&dkg.get_responses().call().await?)?;
+    let justifications: Vec<BundledJustification<C>> =
+        parse_bundle(&dkg.get_justifications().call().await?)?;
+    let complaints: usize = responses.iter().map(|bundle| bundle.responses.len()).sum();
+    println!(
+        "{} of {} participants dealt shares. Got {} complaints and {} justifications.",
+        shares.len(),
+        group.len(),
+        complaints,
+        justifications.len()
+    );
+
+    let output = observe_output(&group, &shares, &responses, &justifications)
+        .map_err(|err| anyhow::anyhow!("The DKG failed: {}", err))?;
+
+    let qualified = output
+        .qual
+        .nodes
+        .iter()
+        .map(|node| registered[node.id() as usize])
+        .collect::<Vec<_>>();
+    for address in registered.iter().filter(|a| !qualified.contains(a)) {
+        println!("{:?} was disqualified", address);
+    }
+
+    let public_key = hex::encode(&bincode::serialize(&output.public.public_key())?);
+    progress.emit(ProgressEvent::Finished {
+        public_key: public_key.clone(),
+    });
+
+    let output = ObservedJson {
+        public_key,
+        public_polynomial: hex::encode(&bincode::serialize(&output.public)?),
+        qualified,
+    };
+    if let Some(path) = opts.output_path {
+        let file = File::create(path)?;
+        serde_json::to_writer(&file, &output)?;
+    } else {
+        serde_json::to_writer(std::io::stdout(), &output)?;
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize, Debug)]
+struct ObservedJson {
+    #[serde(rename = "publicKey")]
+    public_key: String,
+    #[serde(rename = "publicPolynomial")]
+    public_polynomial: String,
+    qualified: Vec<Address>,
+}
+
 #[derive(serde::Serialize, Debug)]
 struct OutputJson {
     #[serde(rename = "publicKey")]
@@ -226,27 +589,71 @@ struct OutputJson {
     share: String,
 }
 
-async fn wait_for_phase<P: JsonRpcClient, S: Signer>(
-    dkg: &DKGContract<P, S>,
+async fn wait_for_phase<P: JsonRpcClient, S: Signer, G: ProgressSink>(
+    dkg: &DKGBoard<P, S>,
     num: u64,
-) -> Result<(), ContractError> {
-    println!("Waiting for Phase {} to start", num);
+    progress: &mut G,
+    hooks: &Hooks,
+) -> Result<()> {
+    progress.emit(ProgressEvent::WaitingForPhase(num));
 
     loop {
         let phase = dkg.in_phase().call().await?;
         if phase.as_u64() == num {
             break;
         }
-        print!(".");
+        progress.emit(ProgressEvent::StillWaiting);
         // 6s for 1 Celo block
         tokio::time::delay_for(std::time::Duration::from_millis(6000)).await;
     }
 
-    println!("\nIn Phase {}. Moving to the next step.", num);
+    progress.emit(ProgressEvent::PhaseEntered(num));
+
+    if hooks.on_phase.is_some() {
+        let block = dkg.contract().client().get_block_number().await?;
+        hooks.phase_entered(num, block, dkg.contract().address());
+    }
+
+    Ok(())
+}
+
+/// Creates a file only its owner can read and write
+fn create_private_file(path: &str) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    options.open(path)
+}
 
+fn write_gas_report(report: &GasReport, path: Option<String>) -> Result<()> {
+    report.write_table(std::io::stderr())?;
+    if let Some(path) = path {
+        let file = File::create(path)?;
+        report.write_json(&file)?;
+    }
     Ok(())
 }
 
+fn parse_group<C: Curve>((threshold, keys): (U256, Vec<Vec<u8>>)) -> Result<Group<C>> {
+    let nodes = keys
+        .into_iter()
+        .filter(|pubkey| !pubkey.is_empty()) // skip users that did not register
+        .enumerate()
+        .map(|(i, pubkey)| {
+            let pubkey: C::Point = bincode::deserialize(&pubkey)?;
+            Ok(Node::<C>::new(i as Idx, pubkey))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(Group {
+        threshold: threshold.as_u64() as usize,
+        nodes,
+    })
+}
+
 fn parse_bundle<D: serde::de::DeserializeOwned>(bundle: &[Vec<u8>]) -> Result<Vec<D>> {
     bundle
         .iter()
@@ -0,0 +1,393 @@
+use crate::{
+    coordinator_contract::Coordinator,
+    dkg_contract::DKG,
+    gas::GasReport,
+    progress::{ProgressEvent, ProgressSink},
+    DKGContractError,
+};
+use async_trait::async_trait;
+use ethers::{
+    contract::{builders::ContractCall, Contract},
+    providers::JsonRpcClient,
+    signers::{Client, Signer},
+    types::{Address, TransactionReceipt, H256, U256},
+};
+use std::str::FromStr;
+
+/// How many times `DKGBoard::send` submits a transaction before giving up
+pub const MAX_SEND_ATTEMPTS: usize = 3;
+
+/// Why a transaction could not be sent. Reverts come back as RPC errors, so they are
+/// told apart by their message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SendFailure {
+    /// We published for this phase already, the board reverts a second time
+    AlreadyPublished,
+    /// The contract rejected the transaction, sending it again won't help
+    Reverted,
+    /// The node could not be reached or answered with an error, worth retrying
+    Transient,
+}
+
+impl SendFailure {
+    pub fn classify(message: &str) -> Self {
+        if message.contains("already published") {
+            SendFailure::AlreadyPublished
+        } else if message.contains("revert") {
+            SendFailure::Reverted
+        } else {
+            SendFailure::Transient
+        }
+    }
+}
+
+/// Broadcasts transactions and follows them until they are mined, so that the hash of a
+/// transaction is known before its receipt
+#[async_trait(?Send)]
+pub trait TxSubmitter<P, S> {
+    /// Signs and broadcasts the call, returning the hash of its transaction right away
+    async fn submit(&self, call: &ContractCall<P, S, H256>) -> Result<H256, DKGContractError>;
+
+    /// Waits for the transaction to be mined
+    async fn confirm(&self, tx_hash: H256) -> Result<TransactionReceipt, DKGContractError>;
+}
+
+/// The phases participants publish in, numbered like the board's `inPhase`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Shares,
+    Responses,
+    Justifications,
+}
+
+impl Phase {
+    pub fn number(self) -> U256 {
+        match self {
+            Phase::Shares => 1.into(),
+            Phase::Responses => 2.into(),
+            Phase::Justifications => 3.into(),
+        }
+    }
+}
+
+/// The layout of the contract coordinating the ceremony
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContractKind {
+    /// `DKG.sol`: participants get allowlisted and register their BLS key themselves
+    DKG,
+    /// `Coordinator.sol`: one board per group epoch, initialized by its owner with the
+    /// participants and their BLS keys
+    Coordinator,
+}
+
+impl FromStr for ContractKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dkg" => Ok(ContractKind::DKG),
+            "coordinator" => Ok(ContractKind::Coordinator),
+            _ => Err(format!(
+                "unknown contract kind `{}`, expected `dkg` or `coordinator`",
+                s
+            )),
+        }
+    }
+}
+
+impl Default for ContractKind {
+    fn default() -> Self {
+        ContractKind::DKG
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum BoardContract<P, S> {
+    DKG(DKG<P, S>),
+    Coordinator(Coordinator<P, S>),
+}
+
+impl<P: JsonRpcClient, S: Signer> BoardContract<P, S> {
+    pub fn contract(&self) -> &Contract<P, S> {
+        match self {
+            BoardContract::DKG(contract) => contract,
+            BoardContract::Coordinator(contract) => contract,
+        }
+    }
+}
+
+/// A DKG board backed by either contract layout. Both have the same phases and getters,
+/// so the ceremony runs the same way on each of them.
+#[derive(Clone, Debug)]
+pub struct DKGBoard<P, S> {
+    pub board_contract: BoardContract<P, S>,
+    /// The gas used by the transactions sent to the board, if it is being reported
+    pub gas_report: Option<GasReport>,
+    /// What happened while publishing, until `flush_progress` hands it to a sink
+    pub progress_events: Vec<ProgressEvent>,
+}
+
+impl<P: JsonRpcClient, S: Signer> DKGBoard<P, S> {
+    pub fn new(kind: ContractKind, address: Address, client: Client<P, S>) -> Self {
+        let board_contract = match kind {
+            ContractKind::DKG => BoardContract::DKG(DKG::new(address, client)),
+            ContractKind::Coordinator => {
+                BoardContract::Coordinator(Coordinator::new(address, client))
+            }
+        };
+
+        DKGBoard {
+            board_contract,
+            gas_report: None,
+            progress_events: vec![],
+        }
+    }
+
+    /// Reports what happened while publishing since the last call
+    pub fn flush_progress<G: ProgressSink>(&mut self, progress: &mut G) {
+        for event in self.progress_events.drain(..) {
+            progress.emit(event);
+        }
+    }
+
+    pub fn kind(&self) -> ContractKind {
+        match self.board_contract {
+            BoardContract::DKG(_) => ContractKind::DKG,
+            BoardContract::Coordinator(_) => ContractKind::Coordinator,
+        }
+    }
+
+    pub fn contract(&self) -> &Contract<P, S> {
+        self.board_contract.contract()
+    }
+
+    /// Sends the transaction and waits for it to be mined, recording its gas
+    /// under `action` if gas is being reported. Its hash and then its receipt are
+    /// reported as progress.
+    ///
+    /// The call is simulated before every attempt. A revert because we already published
+    /// (e.g. an earlier attempt got mined after all) returns None and any other revert
+    /// fails right away, while RPC failures are retried up to `MAX_SEND_ATTEMPTS` times.
+    ///
+    /// A publish is pinned to the `phase` it was built for: every attempt first returns None
+    /// if our entry for that phase is on the board, and fails if the board moved on, so that
+    /// a retry never lands in the slot of the next phase.
+    pub async fn send(
+        &mut self,
+        action: &str,
+        phase: Option<Phase>,
+        call: ContractCall<P, S, H256>,
+    ) -> Result<Option<TransactionReceipt>, DKGContractError> {
+        let mut tx_hash = None;
+        let mut reason = String::new();
+
+        for attempt in 1..=MAX_SEND_ATTEMPTS {
+            if attempt > 1 {
+                eprintln!(
+                    "Sending `{}` failed ({}), retrying ({}/{})",
+                    action, reason, attempt, MAX_SEND_ATTEMPTS
+                );
+                // 6s for 1 Celo block
+                tokio::time::delay_for(std::time::Duration::from_millis(6000)).await;
+            }
+
+            if let Some(phase) = phase {
+                match self.entry(phase, self.address()).call().await {
+                    Ok(entry) if !entry.is_empty() => return Ok(None),
+                    Ok(_) => {}
+                    Err(err) => {
+                        reason = err.to_string();
+                        continue;
+                    }
+                }
+
+                // `inPhase` reverts once the DKG has ended
+                match self.in_phase().call().await {
+                    Ok(current) if current == phase.number() => {}
+                    Ok(current) => {
+                        reason = format!("the board moved on to phase {}", current);
+                        break;
+                    }
+                    Err(err) => {
+                        reason = err.to_string();
+                        match SendFailure::classify(&reason) {
+                            SendFailure::Transient => continue,
+                            _ => break,
+                        }
+                    }
+                }
+            }
+
+            if let Err(err) = call.call().await {
+                reason = err.to_string();
+                match SendFailure::classify(&reason) {
+                    SendFailure::AlreadyPublished => return Ok(None),
+                    SendFailure::Reverted => break,
+                    // decoding the empty output of a call can fail too, sending tells
+                    SendFailure::Transient => {}
+                }
+            }
+
+            let hash = match self.submit(&call).await {
+                Ok(hash) => hash,
+                Err(err) => {
+                    reason = err.to_string();
+                    continue;
+                }
+            };
+            tx_hash = Some(hash);
+            self.progress_events.push(ProgressEvent::Submitted {
+                action: action.to_owned(),
+                tx_hash: hash,
+            });
+
+            let receipt = match self.confirm(hash).await {
+                Ok(receipt) => receipt,
+                Err(err) => {
+                    reason = err.to_string();
+                    continue;
+                }
+            };
+            // the next simulation tells why
+            if receipt.status == Some(0.into()) {
+                reason = "the transaction reverted".to_owned();
+                continue;
+            }
+            self.progress_events.push(ProgressEvent::Confirmed {
+                action: action.to_owned(),
+                tx_hash: hash,
+                block_number: receipt.block_number,
+                gas_used: receipt.gas_used,
+            });
+
+            if let Some(ref mut gas_report) = self.gas_report {
+                let client = self.board_contract.contract().client();
+                gas_report.record(client, action, &receipt).await?;
+            }
+            return Ok(Some(receipt));
+        }
+
+        Err(DKGContractError::SendFailed {
+            action: action.to_owned(),
+            tx_hash,
+            reason,
+        })
+    }
+
+    /// The address the board's transactions are sent from
+    pub fn address(&self) -> Address {
+        self.contract().client().address()
+    }
+
+    pub fn publish(&self, value: Vec<u8>) -> ContractCall<P, S, H256> {
+        match &self.board_contract {
+            BoardContract::DKG(contract) => contract.publish(value),
+            BoardContract::Coordinator(contract) => contract.publish(value),
+        }
+    }
+
+    pub fn in_phase(&self) -> ContractCall<P, S, U256> {
+        match &self.board_contract {
+            BoardContract::DKG(contract) => contract.in_phase(),
+            BoardContract::Coordinator(contract) => contract.in_phase(),
+        }
+    }
+
+    pub fn get_bls_keys(&self) -> ContractCall<P, S, (U256, Vec<Vec<u8>>)> {
+        match &self.board_contract {
+            BoardContract::DKG(contract) => contract.get_bls_keys(),
+            BoardContract::Coordinator(contract) => contract.get_bls_keys(),
+        }
+    }
+
+    pub fn get_participants(&self) -> ContractCall<P, S, Vec<Address>> {
+        match &self.board_contract {
+            BoardContract::DKG(contract) => contract.get_participants(),
+            BoardContract::Coordinator(contract) => contract.get_participants(),
+        }
+    }
+
+    pub fn get_shares(&self) -> ContractCall<P, S, Vec<Vec<u8>>> {
+        match &self.board_contract {
+            BoardContract::DKG(contract) => contract.get_shares(),
+            BoardContract::Coordinator(contract) => contract.get_shares(),
+        }
+    }
+
+    pub fn get_responses(&self) -> ContractCall<P, S, Vec<Vec<u8>>> {
+        match &self.board_contract {
+            BoardContract::DKG(contract) => contract.get_responses(),
+            BoardContract::Coordinator(contract) => contract.get_responses(),
+        }
+    }
+
+    pub fn get_justifications(&self) -> ContractCall<P, S, Vec<Vec<u8>>> {
+        match &self.board_contract {
+            BoardContract::DKG(contract) => contract.get_justifications(),
+            BoardContract::Coordinator(contract) => contract.get_justifications(),
+        }
+    }
+
+    /// What the participant published in the phase, empty if nothing
+    pub fn entry(&self, phase: Phase, participant: Address) -> ContractCall<P, S, Vec<u8>> {
+        match phase {
+            Phase::Shares => self.shares(participant),
+            Phase::Responses => self.responses(participant),
+            Phase::Justifications => self.justifications(participant),
+        }
+    }
+
+    pub fn shares(&self, participant: Address) -> ContractCall<P, S, Vec<u8>> {
+        match &self.board_contract {
+            BoardContract::DKG(contract) => contract.shares(participant),
+            BoardContract::Coordinator(contract) => contract.shares(participant),
+        }
+    }
+
+    pub fn responses(&self, participant: Address) -> ContractCall<P, S, Vec<u8>> {
+        match &self.board_contract {
+            BoardContract::DKG(contract) => contract.responses(participant),
+            BoardContract::Coordinator(contract) => contract.responses(participant),
+        }
+    }
+
+    pub fn justifications(&self, participant: Address) -> ContractCall<P, S, Vec<u8>> {
+        match &self.board_contract {
+            BoardContract::DKG(contract) => contract.justifications(participant),
+            BoardContract::Coordinator(contract) => contract.justifications(participant),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<P: JsonRpcClient, S: Signer> TxSubmitter<P, S> for DKGBoard<P, S> {
+    async fn submit(&self, call: &ContractCall<P, S, H256>) -> Result<H256, DKGContractError> {
+        Ok(call.clone().send().await?)
+    }
+
+    async fn confirm(&self, tx_hash: H256) -> Result<TransactionReceipt, DKGContractError> {
+        let client = self.board_contract.contract().client();
+        Ok(client.pending_transaction(tx_hash).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_failures_are_told_apart() {
+        assert_eq!(
+            SendFailure::classify("execution reverted: you have already published your shares"),
+            SendFailure::AlreadyPublished
+        );
+        assert_eq!(
+            SendFailure::classify("execution reverted: DKG has ended"),
+            SendFailure::Reverted
+        );
+        assert_eq!(
+            SendFailure::classify("error sending request for url"),
+            SendFailure::Transient
+        );
+    }
+}
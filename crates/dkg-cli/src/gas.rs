@@ -0,0 +1,163 @@
+use ethers::{
+    providers::{JsonRpcClient, ProviderError},
+    signers::{Client, Signer},
+    types::{Address, TransactionReceipt, H256, U256},
+};
+use serde::Serialize;
+use std::{collections::BTreeMap, io::Write};
+
+/// The gas used by a single transaction sent by the CLI
+#[derive(Debug, Clone, Serialize)]
+pub struct GasEntry {
+    pub action: String,
+    pub sender: Address,
+    pub hash: H256,
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U256,
+    #[serde(rename = "gasPrice")]
+    pub gas_price: U256,
+}
+
+impl GasEntry {
+    pub fn cost(&self) -> U256 {
+        self.gas_used * self.gas_price
+    }
+}
+
+/// The gas spent per sender across the transactions of a report
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GasTotal {
+    pub transactions: usize,
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U256,
+    pub cost: U256,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GasReport {
+    pub transactions: Vec<GasEntry>,
+}
+
+#[derive(Serialize)]
+struct GasReportJson<'a> {
+    transactions: &'a [GasEntry],
+    totals: BTreeMap<Address, GasTotal>,
+}
+
+impl GasReport {
+    /// Records the gas used by a mined transaction. The gas price is fetched from the
+    /// transaction itself since the receipt does not carry it.
+    pub async fn record<P: JsonRpcClient, S: Signer>(
+        &mut self,
+        client: &Client<P, S>,
+        action: &str,
+        receipt: &TransactionReceipt,
+    ) -> Result<(), ProviderError> {
+        let tx = client.get_transaction(receipt.transaction_hash).await?;
+
+        self.transactions.push(GasEntry {
+            action: action.to_owned(),
+            sender: tx.from,
+            hash: receipt.transaction_hash,
+            gas_used: receipt.gas_used.unwrap_or_default(),
+            gas_price: tx.gas_price,
+        });
+
+        Ok(())
+    }
+
+    pub fn totals(&self) -> BTreeMap<Address, GasTotal> {
+        let mut totals = BTreeMap::<Address, GasTotal>::new();
+        for entry in &self.transactions {
+            let total = totals.entry(entry.sender).or_default();
+            total.transactions += 1;
+            total.gas_used += entry.gas_used;
+            total.cost += entry.cost();
+        }
+        totals
+    }
+
+    /// Writes a table of the transactions followed by the totals per sender
+    pub fn write_table<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "{:<24} {:<44} {:>12} {:>24}",
+            "action", "sender", "gas used", "cost (wei)"
+        )?;
+        for entry in &self.transactions {
+            writeln!(
+                writer,
+                "{:<24} {:<44?} {:>12} {:>24}",
+                entry.action,
+                entry.sender,
+                entry.gas_used,
+                entry.cost()
+            )?;
+        }
+
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "{:<44} {:>12} {:>12} {:>24}",
+            "sender", "txs", "gas used", "cost (wei)"
+        )?;
+        for (sender, total) in self.totals() {
+            writeln!(
+                writer,
+                "{:<44?} {:>12} {:>12} {:>24}",
+                sender, total.transactions, total.gas_used, total.cost
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_json<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+        let output = GasReportJson {
+            transactions: &self.transactions,
+            totals: self.totals(),
+        };
+        serde_json::to_writer(writer, &output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(action: &str, sender: Address, gas_used: u64, gas_price: u64) -> GasEntry {
+        GasEntry {
+            action: action.to_owned(),
+            sender,
+            hash: H256::zero(),
+            gas_used: gas_used.into(),
+            gas_price: gas_price.into(),
+        }
+    }
+
+    #[test]
+    fn totals_are_per_sender() {
+        let alice = Address::from_low_u64_be(1);
+        let bob = Address::from_low_u64_be(2);
+
+        let report = GasReport {
+            transactions: vec![
+                entry("register", alice, 100, 2),
+                entry("publish", alice, 300, 3),
+                entry("publish", bob, 50, 1),
+            ],
+        };
+
+        let totals = report.totals();
+
+        assert_eq!(
+            totals[&alice],
+            GasTotal {
+                transactions: 2,
+                gas_used: 400.into(),
+                cost: 1100.into(),
+            }
+        );
+        assert_eq!(totals[&bob].cost, 50.into());
+    }
+}
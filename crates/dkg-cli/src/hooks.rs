@@ -0,0 +1,57 @@
+use ethers::types::{Address, U64};
+use std::process::Command;
+
+/// The commands an operator runs as the ceremony advances, e.g. to notify a channel or
+/// back up the DKG state. They are run with `sh -c` and a failing hook is reported
+/// without stopping the ceremony.
+#[derive(Debug, Clone, Default)]
+pub struct Hooks {
+    /// Run with PHASE, BLOCK and CONTRACT set whenever a phase starts
+    pub on_phase: Option<String>,
+    /// Run with CONTRACT set once the DKG succeeded. It gets the output file's path as
+    /// its first argument and in OUTPUT_PATH, unless the output went to stdout
+    pub on_complete: Option<String>,
+}
+
+impl Hooks {
+    pub fn phase_entered(&self, phase: u64, block: U64, contract: Address) {
+        if let Some(ref cmd) = self.on_phase {
+            run_hook(
+                "phase",
+                cmd,
+                &[
+                    ("PHASE", phase.to_string()),
+                    ("BLOCK", block.to_string()),
+                    ("CONTRACT", format!("{:?}", contract)),
+                ],
+                None,
+            );
+        }
+    }
+
+    pub fn completed(&self, output_path: Option<&str>, contract: Address) {
+        if let Some(ref cmd) = self.on_complete {
+            let mut env = vec![("CONTRACT", format!("{:?}", contract))];
+            if let Some(path) = output_path {
+                env.push(("OUTPUT_PATH", path.to_owned()));
+            }
+            run_hook("complete", cmd, &env, output_path);
+        }
+    }
+}
+
+fn run_hook(name: &str, cmd: &str, env: &[(&str, String)], arg: Option<&str>) {
+    let mut command = Command::new("sh");
+    // the name after the command is what it sees as $0
+    command.arg("-c").arg(cmd).arg("dkg-cli-hook");
+    if let Some(arg) = arg {
+        command.arg(arg);
+    }
+    command.envs(env.iter().cloned());
+
+    match command.status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("The {} hook `{}` failed: {}", name, cmd, status),
+        Err(err) => eprintln!("Could not run the {} hook `{}`: {}", name, cmd, err),
+    }
+}
@@ -1,14 +1,22 @@
 pub mod actions;
+pub mod board;
+mod coordinator_contract;
 mod dkg_contract;
+pub mod gas;
+pub mod hooks;
 pub mod opts;
+pub mod progress;
+pub mod roster;
 
 use async_trait::async_trait;
-use dkg_contract::DKG;
+use board::{DKGBoard, Phase};
 use ethers::{
     contract::ContractError,
     providers::{JsonRpcClient, ProviderError},
     signers::Signer,
+    types::H256,
 };
+use progress::ProgressEvent;
 
 use dkg_core::{
     primitives::{BundledJustification, BundledResponses, BundledShares},
@@ -25,19 +33,33 @@ pub enum DKGContractError {
     PublishingError(#[from] ContractError),
     #[error(transparent)]
     ProviderError(#[from] ProviderError),
+    #[error("`{action}` failed (last transaction: {tx_hash:?}): {reason}")]
+    SendFailed {
+        action: String,
+        tx_hash: Option<H256>,
+        reason: String,
+    },
 }
 
 #[async_trait(?Send)]
-impl<C: Curve, P: JsonRpcClient, S: Signer> BoardPublisher<C> for DKG<P, S> {
+impl<C: Curve, P: JsonRpcClient, S: Signer> BoardPublisher<C> for DKGBoard<P, S> {
     type Error = DKGContractError;
 
     async fn publish_shares(&mut self, shares: BundledShares<C>) -> Result<(), Self::Error>
     where
         C: 'async_trait,
     {
+        // a node restarted within the phase has already published
+        if !self.shares(self.address()).call().await?.is_empty() {
+            self.progress_events
+                .push(ProgressEvent::AlreadyPublished(Phase::Shares));
+            return Ok(());
+        }
+
         let serialized = bincode::serialize(&shares)?;
-        let pending_tx = self.publish(serialized).send().await?;
-        let _tx_receipt = self.pending_transaction(pending_tx).await?;
+        let call = self.publish(serialized);
+        self.send("publish shares", Some(Phase::Shares), call)
+            .await?;
         Ok(())
     }
 
@@ -45,9 +67,16 @@ impl<C: Curve, P: JsonRpcClient, S: Signer> BoardPublisher<C> for DKG<P, S> {
     where
         C: 'async_trait,
     {
+        if !self.responses(self.address()).call().await?.is_empty() {
+            self.progress_events
+                .push(ProgressEvent::AlreadyPublished(Phase::Responses));
+            return Ok(());
+        }
+
         let serialized = bincode::serialize(&responses)?;
-        let pending_tx = self.publish(serialized).send().await?;
-        let _tx_receipt = self.pending_transaction(pending_tx).await?;
+        let call = self.publish(serialized);
+        self.send("publish responses", Some(Phase::Responses), call)
+            .await?;
         Ok(())
     }
 
@@ -58,9 +87,16 @@ impl<C: Curve, P: JsonRpcClient, S: Signer> BoardPublisher<C> for DKG<P, S> {
     where
         C: 'async_trait,
     {
+        if !self.justifications(self.address()).call().await?.is_empty() {
+            self.progress_events
+                .push(ProgressEvent::AlreadyPublished(Phase::Justifications));
+            return Ok(());
+        }
+
         let serialized = bincode::serialize(&justifications)?;
-        let pending_tx = self.publish(serialized).send().await?;
-        let _tx_receipt = self.pending_transaction(pending_tx).await?;
+        let call = self.publish(serialized);
+        self.send("publish justifications", Some(Phase::Justifications), call)
+            .await?;
         Ok(())
     }
 }
@@ -18,11 +18,22 @@ async fn main() -> anyhow::Result<()> {
     let rng = &mut rand::thread_rng();
 
     match command {
-        Command::Keygen(opts) => keygen(opts, rng)?,
+        Command::Keygen(opts) => keygen::<Scheme, _>(opts, rng)?,
         Command::Run(opts) => run::<Scheme, Curve, _>(opts, rng).await?,
         Command::Start(opts) => start(opts).await?,
         Command::Deploy(opts) => deploy(opts).await?,
         Command::Allow(opts) => allow(opts).await?,
+        Command::Recover(opts) => recover::<Curve>(opts).await?,
+        Command::Observe(opts) => observe::<Curve>(opts).await?,
+        Command::Roster(opts) => match opts.command {
+            Some(RosterCommand::Export(opts)) => roster_export(opts)?,
+            Some(RosterCommand::Import(opts)) => roster_import(opts).await?,
+            None => {
+                eprintln!("No roster command was provided.");
+                eprintln!("{}", RosterOpts::usage());
+                process::exit(2)
+            }
+        },
     };
 
     Ok(())
@@ -1,3 +1,4 @@
+use crate::board::ContractKind;
 use ethers::types::Address;
 use gumdrop::Options;
 use std::default::Default;
@@ -26,6 +27,15 @@ pub enum Command {
 
     #[options(help = "allow 1 or more DKG participants")]
     Allow(AllowlistOpts),
+
+    #[options(help = "recovers your share from the board of a DKG you missed part of")]
+    Recover(RecoverOpts),
+
+    #[options(help = "follows a DKG you are not part of and reports its public key")]
+    Observe(ObserveOpts),
+
+    #[options(help = "exports or checks the roster of the participants' BLS public keys")]
+    Roster(RosterOpts),
 }
 
 #[derive(Debug, Options, Clone)]
@@ -51,10 +61,57 @@ pub struct DKGConfig {
     #[options(help = "the DKG contract's address")]
     pub contract_address: Address,
 
+    #[options(
+        help = "the layout of the DKG contract: `dkg` (participants register themselves) or `coordinator` (initialized by its owner)",
+        default = "dkg"
+    )]
+    pub contract_kind: ContractKind,
+
+    #[options(
+        help = "your hex encoded BLS private key (hint: `keygen` generates one). Required with a coordinator, which already holds your BLS public key; a new one is generated otherwise"
+    )]
+    pub bls_private_key: Option<String>,
+
     #[options(
         help = "the path where the resulting of the DKG will be stored (stdout if none provided)"
     )]
     pub output_path: Option<String>,
+
+    #[options(
+        help = "the path where the DKG state is kept so that the DKG can be resumed if the CLI is restarted (pass --bls-private-key along with it)"
+    )]
+    pub state_path: Option<String>,
+
+    #[options(help = "print the gas used by every transaction sent and the totals per sender")]
+    pub report_gas: bool,
+
+    #[options(
+        help = "the path where the gas report will be written as JSON (implies --report-gas)"
+    )]
+    pub gas_report_path: Option<String>,
+
+    #[options(
+        help = "path to a roster exported with `roster export`, checked against the registered keys before the DKG starts"
+    )]
+    pub roster_path: Option<String>,
+
+    #[options(
+        help = "path to a file listing the expected participants' addresses, one per line. The DKG is aborted if the registered participants differ"
+    )]
+    pub expect_participants: Option<String>,
+
+    #[options(help = "accept the group without asking for confirmation")]
+    pub yes: bool,
+
+    #[options(
+        help = "a command run whenever a phase starts, with PHASE, BLOCK and CONTRACT set in its environment"
+    )]
+    pub on_phase_hook: Option<String>,
+
+    #[options(
+        help = "a command run once the DKG succeeded, with the output path as its first argument and in OUTPUT_PATH"
+    )]
+    pub on_complete_hook: Option<String>,
 }
 
 #[derive(Debug, Options, Clone)]
@@ -90,6 +147,14 @@ pub struct StartOpts {
 
     #[options(help = "the DKG contract's address")]
     pub contract_address: Address,
+
+    #[options(help = "print the gas used by every transaction sent and the totals per sender")]
+    pub report_gas: bool,
+
+    #[options(
+        help = "the path where the gas report will be written as JSON (implies --report-gas)"
+    )]
+    pub gas_report_path: Option<String>,
 }
 
 #[derive(Debug, Options, Clone)]
@@ -109,4 +174,134 @@ pub struct AllowlistOpts {
 
     #[options(help = "the DKG contract's address")]
     pub contract_address: Address,
+
+    #[options(help = "print the gas used by every transaction sent and the totals per sender")]
+    pub report_gas: bool,
+
+    #[options(
+        help = "the path where the gas report will be written as JSON (implies --report-gas)"
+    )]
+    pub gas_report_path: Option<String>,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct RecoverOpts {
+    help: bool,
+
+    #[options(help = "the celo node's endpoint")]
+    pub node_url: String,
+
+    #[options(
+        help = "path to your celo private key (hint: use the `keygen` command to generate a new one if you don't have one)"
+    )]
+    pub private_key: String,
+
+    #[options(help = "the DKG contract's address")]
+    pub contract_address: Address,
+
+    #[options(
+        help = "the layout of the DKG contract: `dkg` (participants register themselves) or `coordinator` (initialized by its owner)",
+        default = "dkg"
+    )]
+    pub contract_kind: ContractKind,
+
+    #[options(help = "your hex encoded BLS private key, the one you took part in the DKG with")]
+    pub bls_private_key: String,
+
+    #[options(
+        help = "the path where the resulting of the DKG will be stored (stdout if none provided)"
+    )]
+    pub output_path: Option<String>,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct RosterOpts {
+    help: bool,
+
+    #[options(command)]
+    pub command: Option<RosterCommand>,
+}
+
+#[derive(Debug, Options, Clone)]
+pub enum RosterCommand {
+    #[options(help = "signs a roster of the participants' addresses and BLS public keys")]
+    Export(RosterExportOpts),
+
+    #[options(help = "checks a roster's signature and that the registered keys match it")]
+    Import(RosterImportOpts),
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct RosterExportOpts {
+    help: bool,
+
+    #[options(help = "path to the celo private key the roster is signed with")]
+    pub private_key: String,
+
+    #[options(
+        help = "a participant as `address=bls_public_key`, both hex encoded (hint: `keygen` prints both)"
+    )]
+    pub participant: Vec<String>,
+
+    #[options(
+        help = "path to the file where the roster will be written (stdout if none provided)"
+    )]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct RosterImportOpts {
+    help: bool,
+
+    #[options(help = "the celo node's endpoint")]
+    pub node_url: String,
+
+    #[options(
+        help = "path to your celo private key (hint: use the `keygen` command to generate a new one if you don't have one)"
+    )]
+    pub private_key: String,
+
+    #[options(help = "the DKG contract's address")]
+    pub contract_address: Address,
+
+    #[options(
+        help = "the layout of the DKG contract: `dkg` (participants register themselves) or `coordinator` (initialized by its owner)",
+        default = "dkg"
+    )]
+    pub contract_kind: ContractKind,
+
+    #[options(help = "path to the roster")]
+    pub roster_path: String,
+
+    #[options(
+        help = "the address the roster must be signed by (any signer is accepted if none provided)"
+    )]
+    pub signer: Option<Address>,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct ObserveOpts {
+    help: bool,
+
+    #[options(help = "the celo node's endpoint")]
+    pub node_url: String,
+
+    #[options(
+        help = "path to your celo private key (hint: use the `keygen` command to generate a new one if you don't have one)"
+    )]
+    pub private_key: String,
+
+    #[options(help = "the DKG contract's address")]
+    pub contract_address: Address,
+
+    #[options(
+        help = "the layout of the DKG contract: `dkg` (participants register themselves) or `coordinator` (initialized by its owner)",
+        default = "dkg"
+    )]
+    pub contract_kind: ContractKind,
+
+    #[options(
+        help = "the path where the public key and the qualified participants will be stored (stdout if none provided)"
+    )]
+    pub output_path: Option<String>,
 }
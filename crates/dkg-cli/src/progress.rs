@@ -0,0 +1,106 @@
+use crate::board::Phase;
+use ethers::types::{H256, U256, U64};
+use std::io::Write;
+
+/// The steps of a ceremony reported by `run_with_progress`, so that callers embedding
+/// the CLI can follow the DKG without parsing stdout
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    Registering,
+    /// Our BLS key is registered already, an interrupted run is being resumed
+    AlreadyRegistered,
+    /// The path of the DKG state an interrupted run is resumed from
+    ResumingFromState(String),
+    /// What we publish in the phase is on the board already
+    AlreadyPublished(Phase),
+    /// A transaction was broadcast, it may not be mined yet
+    Submitted {
+        action: String,
+        tx_hash: H256,
+    },
+    /// A transaction was mined without reverting
+    Confirmed {
+        action: String,
+        tx_hash: H256,
+        block_number: Option<U64>,
+        gas_used: Option<U256>,
+    },
+    WaitingForPhase(u64),
+    /// The phase we are waiting for has not started yet
+    StillWaiting,
+    PhaseEntered(u64),
+    /// The number of valid share bundles on the board
+    SharesReceived(usize),
+    /// The number of complaints in the responses on the board
+    Complaints(usize),
+    JustificationsReceived(usize),
+    /// The hex encoded threshold public key
+    Finished {
+        public_key: String,
+    },
+}
+
+pub trait ProgressSink {
+    fn emit(&mut self, event: ProgressEvent);
+}
+
+impl<F: FnMut(ProgressEvent)> ProgressSink for F {
+    fn emit(&mut self, event: ProgressEvent) {
+        self(event)
+    }
+}
+
+/// Prints the progress for a user following the ceremony in a terminal
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutProgress;
+
+impl ProgressSink for StdoutProgress {
+    fn emit(&mut self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::Registering => println!("Registering..."),
+            ProgressEvent::AlreadyRegistered => println!("Already registered, resuming."),
+            ProgressEvent::ResumingFromState(path) => {
+                println!("Resuming from the DKG state at {}", path)
+            }
+            ProgressEvent::AlreadyPublished(phase) => {
+                let published = match phase {
+                    Phase::Shares => "shares",
+                    Phase::Responses => "responses",
+                    Phase::Justifications => "justifications",
+                };
+                println!(
+                    "Our {} are already on the board, resuming without publishing them again.",
+                    published
+                )
+            }
+            ProgressEvent::Submitted { action, tx_hash } => {
+                println!("Sent `{}` in transaction {:?}", action, tx_hash)
+            }
+            ProgressEvent::Confirmed {
+                action,
+                block_number,
+                ..
+            } => match block_number {
+                Some(block_number) => println!("`{}` was mined in block {}", action, block_number),
+                None => println!("`{}` was mined", action),
+            },
+            ProgressEvent::WaitingForPhase(num) => println!("Waiting for Phase {} to start", num),
+            ProgressEvent::StillWaiting => {
+                print!(".");
+                let _ = std::io::stdout().flush();
+            }
+            ProgressEvent::PhaseEntered(num) => {
+                println!("\nIn Phase {}. Moving to the next step.", num)
+            }
+            ProgressEvent::SharesReceived(n) => println!("Got {} shares. Running Phase 2", n),
+            ProgressEvent::Complaints(0) => println!("There were no complaints. Getting result."),
+            ProgressEvent::Complaints(n) => {
+                println!("There were {} complaints. Running Phase 3.", n)
+            }
+            ProgressEvent::JustificationsReceived(n) => println!("Got {} justifications.", n),
+            ProgressEvent::Finished { .. } => {
+                println!("Success. Your share and threshold pubkey are ready.")
+            }
+        }
+    }
+}
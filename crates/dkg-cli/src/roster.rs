@@ -0,0 +1,143 @@
+use ethers::{
+    signers::Signer,
+    types::{Address, Signature, SignatureError},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, convert::TryFrom};
+use thiserror::Error;
+
+/// The hex encoded BLS public key of every participant of a ceremony, exchanged
+/// out-of-band before it starts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Roster {
+    pub participants: BTreeMap<Address, String>,
+}
+
+/// A roster along with the signature of the participant who exported it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedRoster {
+    pub roster: Roster,
+    pub signer: Address,
+    pub signature: String,
+}
+
+#[derive(Debug, Error)]
+pub enum RosterError {
+    #[error("the roster was not signed by {0:?}")]
+    InvalidSignature(Address),
+    #[error(transparent)]
+    SignatureError(#[from] SignatureError),
+    #[error(transparent)]
+    HexError(#[from] hex::FromHexError),
+    #[error(transparent)]
+    SerializationError(#[from] serde_json::Error),
+    #[error("{0:?} is registered but not in the roster")]
+    Unlisted(Address),
+    #[error("{0:?} is registered with another BLS key than the one in the roster")]
+    KeyMismatch(Address),
+    #[error("{0:?} is in the roster but not registered")]
+    Unregistered(Address),
+}
+
+impl Roster {
+    /// The bytes that get signed. Participants are sorted by address, so that every
+    /// exporter of the same roster signs the same bytes.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, RosterError> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    pub fn sign<S: Signer>(self, signer: &S) -> Result<SignedRoster, RosterError> {
+        let signature = signer.sign_message(self.canonical_bytes()?);
+
+        Ok(SignedRoster {
+            roster: self,
+            signer: signer.address(),
+            signature: hex::encode(signature.to_vec()),
+        })
+    }
+
+    /// Checks the BLS keys registered by `participants` against the roster. Participants
+    /// with an empty key did not register.
+    pub fn check(&self, participants: &[Address], keys: &[Vec<u8>]) -> Result<(), RosterError> {
+        let registered = participants
+            .iter()
+            .zip(keys)
+            .filter(|(_, key)| !key.is_empty())
+            .collect::<BTreeMap<_, _>>();
+
+        for (address, key) in &registered {
+            match self.participants.get(*address) {
+                None => return Err(RosterError::Unlisted(**address)),
+                Some(listed) if *listed != hex::encode(key) => {
+                    return Err(RosterError::KeyMismatch(**address))
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some(address) = self
+            .participants
+            .keys()
+            .find(|address| !registered.contains_key(address))
+        {
+            return Err(RosterError::Unregistered(*address));
+        }
+
+        Ok(())
+    }
+}
+
+impl SignedRoster {
+    /// Returns the roster if it was signed by its signer
+    pub fn verify(&self) -> Result<&Roster, RosterError> {
+        let signature = Signature::try_from(hex::decode(&self.signature)?.as_slice())?;
+
+        if signature.recover(self.roster.canonical_bytes()?)? != self.signer {
+            return Err(RosterError::InvalidSignature(self.signer));
+        }
+
+        Ok(&self.roster)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roster(entries: &[(Address, &[u8])]) -> Roster {
+        Roster {
+            participants: entries
+                .iter()
+                .map(|(address, key)| (*address, hex::encode(key)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn check_matches_registered_keys() {
+        let alice = Address::from_low_u64_be(1);
+        let bob = Address::from_low_u64_be(2);
+        let carol = Address::from_low_u64_be(3);
+
+        let roster = roster(&[(alice, &[1]), (bob, &[2])]);
+
+        // carol did not register, which the roster doesn't need to know about
+        let participants = [alice, bob, carol];
+        assert!(roster
+            .check(&participants, &[vec![1], vec![2], vec![]])
+            .is_ok());
+
+        assert!(matches!(
+            roster.check(&participants, &[vec![1], vec![3], vec![]]),
+            Err(RosterError::KeyMismatch(address)) if address == bob
+        ));
+        assert!(matches!(
+            roster.check(&participants, &[vec![1], vec![2], vec![3]]),
+            Err(RosterError::Unlisted(address)) if address == carol
+        ));
+        assert!(matches!(
+            roster.check(&participants, &[vec![1], vec![], vec![]]),
+            Err(RosterError::Unregistered(address)) if address == bob
+        ));
+    }
+}
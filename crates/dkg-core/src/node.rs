@@ -181,6 +181,7 @@ mod tests {
     use threshold_bls::{
         curve::bls12381::{self, PairingCurve as BLS12_381},
         curve::zexe::{self as bls12_377, PairingCurve as BLS12_377},
+        ecies,
         poly::Idx,
         sig::{BlindThresholdScheme, G1Scheme, G2Scheme, Scheme, SignatureScheme, ThresholdScheme},
     };
@@ -405,6 +406,63 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn dkg_phase3_disqualifies_unjustified_dealer() {
+        let (t, n) = (5, 8);
+        let bad = 0; // the dealer publishing a corrupted share
+
+        let rng = &mut rand::thread_rng();
+        let (mut board, phase0s) = setup::<bls12_377::G1Curve, G1Scheme<BLS12_377>, _>(n, t, rng);
+
+        let mut phase1s = Vec::new();
+        for phase0 in phase0s {
+            phase1s.push(phase0.run(&mut board, rng).await.unwrap());
+        }
+
+        // the share for the next participant does not decrypt, so it complains
+        board.shares[bad].shares[1].secret =
+            ecies::encrypt(&bls12_377::G1Curve::point(), &[1], rng);
+        let shares = board.shares.clone();
+
+        let mut phase2s = Vec::new();
+        for phase1 in phase1s {
+            phase2s.push(phase1.run(&mut board, &shares).await.unwrap());
+        }
+
+        let responses = board.responses.clone();
+
+        // the dealer goes silent instead of justifying its share
+        let mut phase3s = Vec::new();
+        for (i, phase2) in phase2s.into_iter().enumerate() {
+            if i == bad {
+                let mut silent = InMemoryBoard::<bls12_377::G1Curve>::new();
+                phase2.run(&mut silent, &responses).await.unwrap();
+                continue;
+            }
+
+            match phase2.run(&mut board, &responses).await.unwrap() {
+                Phase2Result::GoToPhase3(p3) => phase3s.push(p3),
+                _ => unreachable!("the complaint must move everyone to phase 3"),
+            }
+        }
+
+        let justifications = board.justifs.clone();
+        assert!(justifications
+            .iter()
+            .all(|justification| justification.dealer_idx != bad as Idx));
+
+        let mut outputs = Vec::new();
+        for phase3 in phase3s {
+            outputs.push(phase3.run(&mut board, &justifications).await.unwrap());
+        }
+
+        // the honest participants agree on a key which the dealer did not contribute to
+        assert!(is_all_same(outputs.iter().map(|output| &output.qual)));
+        assert!(is_all_same(outputs.iter().map(|output| &output.public)));
+        assert!(!outputs[0].qual.contains_index(bad as Idx));
+        assert_eq!(outputs[0].qual.len(), n - 1);
+    }
+
     fn setup<C, S, R: rand::RngCore>(
         n: usize,
         t: usize,
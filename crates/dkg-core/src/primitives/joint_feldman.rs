@@ -5,9 +5,9 @@ use super::common::*;
 use crate::primitives::{
     group::Group,
     phases::{Phase0, Phase1, Phase2, Phase3},
-    status::StatusMatrix,
+    status::{Status, StatusMatrix},
     types::*,
-    DKGError, DKGResult,
+    DKGError, DKGResult, ShareError,
 };
 
 use threshold_bls::{
@@ -358,6 +358,164 @@ where
     }
 }
 
+/// Recovers the output of a finished DKG from the data published during it, for a
+/// participant that missed part of the ceremony (e.g. was offline and never processed
+/// its shares). The qualified set is derived from the published responses and
+/// justifications like the other participants did, and the share is the sum of the
+/// shares the qualified dealers dealt to us.
+///
+/// Fails if one of the qualified dealers dealt us an invalid share, since we did not
+/// complain about it in time to get it justified.
+pub fn recover_output<C: Curve>(
+    private_key: &C::Scalar,
+    group: &Group<C>,
+    bundles: &[BundledShares<C>],
+    responses: &[BundledResponses],
+    justifications: &[BundledJustification<C>],
+) -> DKGResult<DKGOutput<C>> {
+    let mut public_key = C::Point::one();
+    public_key.mul(private_key);
+    let my_idx = group
+        .index(&public_key)
+        .ok_or_else(|| DKGError::PublicKeyNotFound)?;
+
+    let n = group.len();
+    let thr = group.threshold;
+    let bundles = bundles
+        .iter()
+        .filter(|b| group.contains_index(b.dealer_idx))
+        .filter(|b| b.public.degree() == thr - 1)
+        .map(|b| (b.dealer_idx, b))
+        .collect::<HashMap<_, _>>();
+    let publics = bundles
+        .iter()
+        .map(|(&idx, b)| (idx, b.public.clone()))
+        .collect::<PublicInfo<C>>();
+
+    // the other participants' complaints, then ours if we got to publish any
+    let mut statuses = StatusMatrix::new(n, n, Status::Success);
+    set_statuses(my_idx, group, group, &mut statuses, responses);
+    responses
+        .iter()
+        .filter(|r| r.share_idx == my_idx)
+        .flat_map(|r| r.responses.iter())
+        .filter(|r| group.contains_index(r.dealer_idx))
+        .for_each(|r| statuses.set(r.dealer_idx, my_idx, r.status));
+    // dealers which did not publish anything can not be qualified
+    (0..n as Idx)
+        .filter(|idx| !bundles.contains_key(idx))
+        .for_each(|idx| statuses.set(idx, my_idx, Status::Complaint));
+
+    let justified =
+        internal_process_justifications(my_idx, group, &mut statuses, &publics, justifications);
+
+    let qual_indices = (0..n as Idx)
+        .filter(|&dealer| statuses.all_true(dealer))
+        .collect::<Vec<_>>();
+    if qual_indices.len() < thr {
+        return Err(DKGError::NotEnoughJustifications(qual_indices.len(), thr));
+    }
+
+    let mut private = C::Scalar::zero();
+    let mut public = PublicPoly::<C>::zero();
+    for dealer_idx in &qual_indices {
+        let bundle = bundles[dealer_idx];
+        let share = match justified.get(dealer_idx) {
+            Some(share) => share.clone(),
+            None => {
+                let encrypted = bundle
+                    .shares
+                    .iter()
+                    .find(|s| s.share_idx == my_idx)
+                    .ok_or_else(|| ShareError::InvalidShare(*dealer_idx))?;
+                decrypt_and_check_share(
+                    private_key,
+                    my_idx,
+                    *dealer_idx,
+                    &bundle.public,
+                    encrypted,
+                )?
+            }
+        };
+        private.add(&share);
+        public.add(&bundle.public);
+    }
+
+    let qual_nodes = group
+        .nodes
+        .iter()
+        .filter(|n| qual_indices.contains(&n.id()))
+        .cloned()
+        .collect();
+
+    Ok(DKGOutput {
+        qual: Group::new(qual_nodes, thr)?,
+        public,
+        share: Share {
+            index: my_idx,
+            private,
+        },
+    })
+}
+
+/// Derives the qualified set and the distributed public key of a finished DKG from the
+/// data published during it, for an auditor that is not part of the group. Every
+/// published complaint counts, and so does every justification which opens the share
+/// correctly. A dealer that published nothing is disqualified.
+pub fn observe_output<C: Curve>(
+    group: &Group<C>,
+    bundles: &[BundledShares<C>],
+    responses: &[BundledResponses],
+    justifications: &[BundledJustification<C>],
+) -> DKGResult<ObservedOutput<C>> {
+    let n = group.len();
+    let thr = group.threshold;
+    let bundles = bundles
+        .iter()
+        .filter(|b| group.contains_index(b.dealer_idx))
+        .filter(|b| b.public.degree() == thr - 1)
+        .map(|b| (b.dealer_idx, b))
+        .collect::<HashMap<_, _>>();
+    let publics = bundles
+        .iter()
+        .map(|(&idx, b)| (idx, b.public.clone()))
+        .collect::<PublicInfo<C>>();
+
+    // an index outside of the group, so that no response is skipped as our own
+    let observer_idx = n as Idx;
+    let mut statuses = StatusMatrix::new(n, n, Status::Success);
+    set_statuses(observer_idx, group, group, &mut statuses, responses);
+    for dealer in (0..n as Idx).filter(|idx| !bundles.contains_key(idx)) {
+        (0..n as Idx).for_each(|holder| statuses.set(dealer, holder, Status::Complaint));
+    }
+
+    internal_process_justifications(observer_idx, group, &mut statuses, &publics, justifications);
+
+    let qual_indices = (0..n as Idx)
+        .filter(|&dealer| bundles.contains_key(&dealer) && statuses.all_true(dealer))
+        .collect::<Vec<_>>();
+    if qual_indices.len() < thr {
+        return Err(DKGError::NotEnoughJustifications(qual_indices.len(), thr));
+    }
+
+    let mut public = PublicPoly::<C>::zero();
+    for dealer_idx in &qual_indices {
+        public.add(&bundles[dealer_idx].public);
+    }
+
+    let qual_nodes = group
+        .nodes
+        .iter()
+        .filter(|n| qual_indices.contains(&n.id()))
+        .cloned()
+        .collect();
+
+    Ok(ObservedOutput {
+        qual: Group::new(qual_nodes, thr)?,
+        public,
+    })
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -365,6 +523,7 @@ pub mod tests {
         common::tests::{check2, full_dkg, id_out, id_resp, invalid2, invalid_shares, setup_group},
         default_threshold,
     };
+    use rand::thread_rng;
     use std::fmt::Debug;
     use threshold_bls::curve::bls12381::{Curve as BCurve, G1};
 
@@ -420,4 +579,121 @@ pub mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn offline_participant_recovers_output() {
+        let n = 5;
+        let thr = default_threshold(n);
+        let (privs, group) = setup_group::<BCurve>(n, thr);
+
+        // the last participant is offline for the whole ceremony
+        let mut bundles = Vec::new();
+        let dkgs = privs[..n - 1]
+            .iter()
+            .map(|private| {
+                let dkg = DKG::new(private.clone(), group.clone()).unwrap();
+                let (dkg, shares) = dkg.encrypt_shares(&mut thread_rng()).unwrap();
+                bundles.extend(shares);
+                dkg
+            })
+            .collect::<Vec<_>>();
+
+        let mut responses = Vec::new();
+        let dkgs = dkgs
+            .into_iter()
+            .map(|dkg| {
+                let (dkg, bundle) = dkg.process_shares(&bundles, false).unwrap();
+                responses.extend(bundle);
+                dkg
+            })
+            .collect::<Vec<_>>();
+
+        // everyone complains about the missing dealer, so the DKG goes to phase 3
+        let mut justifications = Vec::new();
+        let outputs = dkgs
+            .into_iter()
+            .map(|dkg| match dkg.process_responses(&responses) {
+                Err(Ok((dkg, justification))) => {
+                    justifications.extend(justification);
+                    dkg
+                }
+                _ => panic!("the DKG should go to phase 3"),
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|dkg| dkg.process_justifications(&justifications).unwrap())
+            .collect::<Vec<_>>();
+
+        let recovered =
+            recover_output(&privs[n - 1], &group, &bundles, &responses, &justifications).unwrap();
+        assert_eq!(recovered.public, outputs[0].public);
+        assert_eq!(recovered.qual, outputs[0].qual);
+        assert!(share_correct::<BCurve>(
+            recovered.share.index,
+            &recovered.share.private,
+            &recovered.public
+        ));
+
+        // a participant which did take part recovers the share it already has
+        let recovered =
+            recover_output(&privs[0], &group, &bundles, &responses, &justifications).unwrap();
+        assert_eq!(recovered.share.private, outputs[0].share.private);
+    }
+
+    #[test]
+    fn observer_derives_the_public_output() {
+        // large enough for the threshold to be met without two of the dealers
+        let n = 7;
+        let thr = default_threshold(n);
+        let (privs, group) = setup_group::<BCurve>(n, thr);
+
+        // the last participant is offline, the one before deals an invalid share to
+        // the first participant and doesn't justify it
+        let bad = n - 2;
+        let mut bundles = Vec::new();
+        let dkgs = privs[..n - 1]
+            .iter()
+            .map(|private| {
+                let dkg = DKG::new(private.clone(), group.clone()).unwrap();
+                let (dkg, shares) = dkg.encrypt_shares(&mut thread_rng()).unwrap();
+                bundles.extend(shares);
+                dkg
+            })
+            .collect::<Vec<_>>();
+        bundles[bad].shares[0].secret =
+            threshold_bls::ecies::encrypt(&BCurve::point(), &[1], &mut thread_rng());
+
+        let mut responses = Vec::new();
+        let dkgs = dkgs
+            .into_iter()
+            .map(|dkg| {
+                let (dkg, bundle) = dkg.process_shares(&bundles, false).unwrap();
+                responses.extend(bundle);
+                dkg
+            })
+            .collect::<Vec<_>>();
+
+        let mut justifications = Vec::new();
+        let outputs = dkgs
+            .into_iter()
+            .enumerate()
+            .map(|(i, dkg)| match dkg.process_responses(&responses) {
+                Err(Ok((dkg, justification))) => {
+                    if i != bad {
+                        justifications.extend(justification);
+                    }
+                    dkg
+                }
+                _ => panic!("the DKG should go to phase 3"),
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|dkg| dkg.process_justifications(&justifications).unwrap())
+            .collect::<Vec<_>>();
+
+        let observed = observe_output(&group, &bundles, &responses, &justifications).unwrap();
+        assert_eq!(observed.public, outputs[0].public);
+        assert_eq!(observed.qual, outputs[0].qual);
+        assert_eq!(observed.qual.len(), n - 2);
+    }
 }
@@ -22,6 +22,17 @@ pub struct DKGOutput<C: Curve> {
     pub share: Share<C::Scalar>,
 }
 
+/// ObservedOutput is what anyone can derive from the data published during a
+/// DKG, without holding a share of it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "C::Scalar: DeserializeOwned")]
+pub struct ObservedOutput<C: Curve> {
+    /// The list of nodes that successfully ran the protocol until the end
+    pub qual: Group<C>,
+    /// The distributed public key
+    pub public: PublicPoly<C>,
+}
+
 /// BundledShares holds all encrypted shares a dealer creates during the first
 /// phase of the protocol.
 #[derive(Debug, Clone, Serialize, Deserialize)]
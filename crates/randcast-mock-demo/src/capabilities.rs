@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+/// The major version of the protocol run between nodes and the controller. Nodes of
+/// another major version can't take part in the same DKG.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A curve along with the group signatures are made on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SchemeId {
+    BLS12381G1,
+    BLS12381G2,
+    BLS12377G1,
+    BLS12377G2,
+}
+
+/// Optional services a node offers on top of signing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Feature {
+    Relay,
+    ThresholdDecryption,
+}
+
+/// What a node can run, advertised at registration.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub schemes: BTreeSet<SchemeId>,
+    pub protocol_version: u32,
+    pub features: BTreeSet<Feature>,
+}
+
+impl Default for Capabilities {
+    /// What every node of this version runs: BLS12-381 signatures on G1 and no extras.
+    fn default() -> Self {
+        Capabilities {
+            schemes: vec![SchemeId::BLS12381G1].into_iter().collect(),
+            protocol_version: PROTOCOL_VERSION,
+            features: BTreeSet::new(),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum CapabilityError {
+    #[error("the node does not support {0:?}")]
+    UnsupportedScheme(SchemeId),
+    #[error("the node runs protocol version {0}, version {1} is required")]
+    IncompatibleProtocolVersion(u32, u32),
+}
+
+impl Capabilities {
+    /// Checks that the node can take part in the DKGs and signatures of a controller
+    /// running `scheme`.
+    pub fn check(&self, scheme: SchemeId) -> Result<(), CapabilityError> {
+        if self.protocol_version != PROTOCOL_VERSION {
+            return Err(CapabilityError::IncompatibleProtocolVersion(
+                self.protocol_version,
+                PROTOCOL_VERSION,
+            ));
+        }
+
+        if !self.schemes.contains(&scheme) {
+            return Err(CapabilityError::UnsupportedScheme(scheme));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn check_requires_the_scheme_and_protocol_version() {
+        let mut capabilities = Capabilities::default();
+
+        assert_eq!(capabilities.check(SchemeId::BLS12381G1), Ok(()));
+        assert_eq!(
+            capabilities.check(SchemeId::BLS12377G1),
+            Err(CapabilityError::UnsupportedScheme(SchemeId::BLS12377G1))
+        );
+
+        capabilities.schemes.insert(SchemeId::BLS12377G1);
+
+        assert_eq!(capabilities.check(SchemeId::BLS12377G1), Ok(()));
+
+        capabilities.protocol_version = PROTOCOL_VERSION + 1;
+
+        assert_eq!(
+            capabilities.check(SchemeId::BLS12381G1),
+            Err(CapabilityError::IncompatibleProtocolVersion(
+                PROTOCOL_VERSION + 1,
+                PROTOCOL_VERSION
+            ))
+        );
+    }
+}
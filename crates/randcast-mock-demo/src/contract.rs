@@ -1,11 +1,38 @@
+use crate::capabilities::{Capabilities, SchemeId};
+use crate::coordinator::{
+    Coordinator, CoordinatorTransactions, CoordinatorViews, DKGTranscript, DEFAULT_PHASE_DURATION,
+};
+use crate::grouping::Grouping;
+use crate::mixing::MixingFunction;
+use crate::rebalance::Move;
+use crate::token::Token;
+use crate::types::{Epoch, GroupIndex, MemberIndex, TaskIndex};
 use dkg_core::primitives::minimum_threshold;
-use paired::bls12_381::G1;
+use paired::bls12_381::{G1, G2};
+use serde::{Deserialize, Serialize};
 use std::cmp::max;
-use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::hash::Hash;
+use thiserror::Error;
+use threshold_bls::group::Element;
+use threshold_bls::poly::{Eval, Idx, LagrangeCache};
 use threshold_bls::schemes::bls12_381::G1Scheme as SigScheme;
-use threshold_bls::sig::SignatureScheme;
+use threshold_bls::sig::{Scheme, SignatureScheme, ThresholdScheme};
+
+/// The account holding the stakes, out of which rewards are claimed.
+pub const CONTROLLER_ADDRESS: &str = "0xcontroller";
+
+pub const NODE_STAKING_AMOUNT: usize = 50000;
+
+pub const REQUEST_FEE: usize = 1000;
+
+pub const REQUEST_FEE_PER_WORD: usize = 100;
+
+// the percentages of a request fee paid to the committer and shared among the
+// members who signed, the rest goes to the treasury
+pub const COMMITTER_FEE_SHARE: usize = 20;
+
+pub const MEMBERS_FEE_SHARE: usize = 50;
 
 pub const REWARD_PER_SIGNATURE: usize = 50;
 
@@ -15,43 +42,239 @@ pub const COMMITTER_PENALTY_PER_SIGNATURE: usize = 1000;
 
 pub const CHALLENGE_REWARD_PER_SIGNATURE: usize = 300;
 
+/// Slashed from a member that didn't commit to a DKG that missed its deadline.
+pub const DKG_ABSENCE_PENALTY: usize = 1000;
+
 pub const DEFAULT_MINIMUM_THRESHOLD: usize = 3;
 
+/// The percentage of tasks a member must have signed to commit, with participation
+/// weighting on.
+pub const MIN_COMMITTER_SIGNING_RATE: usize = 50;
+
+pub const GROUP_MAX_CAPACITY: usize = 10;
+
+pub const IDEAL_NUMBER_OF_GROUPS: usize = 5;
+
+pub const DEFAULT_COORDINATOR_RETENTION: usize = 100;
+
+/// How many sets of signers the controller keeps the Lagrange coefficients of, so that
+/// fulfillments signed by the same members don't compute them again.
+pub const LAGRANGE_CACHE_CAPACITY: usize = 16;
+
+/// Blocks a group waits for its DKG to reach consensus before the DKG is run again,
+/// leaving room for the phases of the coordinator and the commits after them.
+pub const DEFAULT_DKG_COMMIT_DEADLINE: usize = 5 * DEFAULT_PHASE_DURATION;
+
+/// What the controller does when a group drops below the minimum size and
+/// rebalancing can't bring it back up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebalanceFailurePolicy {
+    /// Keeps the members in the group, which stays inactive until enough nodes join it.
+    LeavePending,
+    /// Moves the members to the smallest other groups that still have room.
+    Scatter,
+}
+
+/// How large a group can grow and the lowest threshold it runs a DKG with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GroupConfig {
+    pub minimum_threshold: usize,
+    pub capacity: usize,
+}
+
+impl Default for GroupConfig {
+    fn default() -> Self {
+        GroupConfig {
+            minimum_threshold: DEFAULT_MINIMUM_THRESHOLD,
+            capacity: GROUP_MAX_CAPACITY,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum GroupConfigError {
+    #[error("a minimum threshold of {0} is below 2")]
+    ThresholdTooLow(usize),
+    #[error("a minimum threshold of {0} exceeds the capacity of {1}")]
+    ThresholdAboveCapacity(usize, usize),
+}
+
+impl GroupConfig {
+    pub fn validate(&self) -> Result<(), GroupConfigError> {
+        if self.minimum_threshold < 2 {
+            return Err(GroupConfigError::ThresholdTooLow(self.minimum_threshold));
+        }
+
+        if self.minimum_threshold > self.capacity {
+            return Err(GroupConfigError::ThresholdAboveCapacity(
+                self.minimum_threshold,
+                self.capacity,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Every map of the controller that group formation iterates over is ordered, so the
+/// same transactions always lead to the same groups:
+/// - groups are visited by index, nodes, members and commitments by id address
+/// - a joining node goes to the group with room picked by the grouping strategy, by
+///   default the smallest one with ties going to the lowest index
+/// - members are renumbered, moved and rejoined in member index order
+#[derive(Clone)]
 pub struct Controller {
+    pub owner: String,
+    pub rebalance_failure_policy: RebalanceFailurePolicy,
+    // which group a joining node goes to and who moves when rebalancing
+    pub grouping: Grouping,
+    // what the outputs, committer seeds and rebalancing picks are derived with
+    pub mixing: MixingFunction,
+    // how many ended coordinators are kept in the archive
+    pub coordinator_retention: usize,
+    // blocks between two beacon rounds, beacon mode is off if None
+    pub beacon_period: Option<usize>,
+    // blocks after which a DKG without consensus is run again, never if None
+    pub dkg_commit_deadline: Option<usize>,
+    // blocks without activity after which a node gets frozen, never if None
+    pub liveness_timeout: Option<usize>,
+    // whether committers and the members' fee share favor members who sign more often
+    pub participation_weighting: bool,
+    // every group signs with it, so only nodes supporting it can register
+    pub scheme: SchemeId,
+    // addresses the owner banned from registering and from groups
+    blacklist: BTreeSet<String>,
+    // the sizing of groups created from now on, unless overridden at creation
+    group_config: GroupConfig,
     pub block_height: usize,
-    pub epoch: usize,
+    pub epoch: Epoch,
     pub signature_count: usize,
     pub last_output: u64,
-    pub last_group_index: usize,
-    groups: HashMap<usize, Group>,
-    nodes: HashMap<String, Node>,
+    pub last_group_index: GroupIndex,
+    groups: BTreeMap<GroupIndex, Group>,
+    nodes: BTreeMap<String, Node>,
+    pub token: Token,
+    // rewards accrued at each reward address, claimable from the controller's balance
     pub rewards: HashMap<String, usize>,
-    pending_signature_tasks: HashMap<usize, SignatureTask>,
-    verifiable_signature_rewards: HashMap<usize, SignatureReward>,
+    // how the rewards of every earner were accrued
+    reward_statements: HashMap<String, RewardStatement>,
+    // how every node took part in the tasks and DKGs of its groups
+    participation: BTreeMap<String, ParticipationStats>,
+    // the protocol's share of the request fees, held in the controller's balance
+    treasury: usize,
+    // ordered by signature index, so it doubles as the queue of unconsumed tasks
+    pending_signature_tasks: BTreeMap<TaskIndex, SignatureTask>,
+    verifiable_signature_rewards: HashMap<TaskIndex, SignatureReward>,
+    // every challenge of a signature reward, in order
+    challenge_events: Vec<ChallengeEvent>,
     // mock for locally test environment
-    dkg_task: Option<DKGTask>,
-    signature_task: Option<SignatureTask>,
+    dkg_tasks: Vec<DKGTask>,
+    // the coordinator of the latest DKG of each group
+    coordinators: BTreeMap<GroupIndex, Coordinator>,
+    // ended coordinators, oldest first
+    archived_coordinators: VecDeque<Coordinator>,
+    // the signature of each beacon round
+    beacons: BTreeMap<usize, Vec<u8>>,
+    // derived from the signers of past fulfillments only, so it is not part of the state
+    lagrange_cache: LagrangeCache<<SigScheme as Scheme>::Private>,
 }
 
 impl Controller {
-    pub fn new(initial_entropy: u64) -> Self {
+    pub fn new(owner: String, initial_entropy: u64) -> Self {
         Controller {
+            owner,
+            rebalance_failure_policy: RebalanceFailurePolicy::LeavePending,
+            grouping: Grouping::SmallestGroup,
+            mixing: MixingFunction::default(),
+            coordinator_retention: DEFAULT_COORDINATOR_RETENTION,
+            beacon_period: None,
+            dkg_commit_deadline: Some(DEFAULT_DKG_COMMIT_DEADLINE),
+            liveness_timeout: None,
+            participation_weighting: false,
+            scheme: SchemeId::BLS12381G1,
+            blacklist: BTreeSet::new(),
+            group_config: GroupConfig::default(),
             block_height: 0,
-            epoch: 1,
+            epoch: Epoch(1),
             signature_count: 0,
             last_output: initial_entropy,
-            last_group_index: 0,
-            groups: HashMap::new(),
-            nodes: HashMap::new(),
+            last_group_index: GroupIndex(0),
+            groups: BTreeMap::new(),
+            nodes: BTreeMap::new(),
+            token: Token::new(),
             rewards: HashMap::new(),
-            pending_signature_tasks: HashMap::new(),
+            reward_statements: HashMap::new(),
+            participation: BTreeMap::new(),
+            treasury: 0,
+            pending_signature_tasks: BTreeMap::new(),
             verifiable_signature_rewards: HashMap::new(),
-            dkg_task: None,
-            signature_task: None,
+            challenge_events: vec![],
+            dkg_tasks: vec![],
+            coordinators: BTreeMap::new(),
+            archived_coordinators: VecDeque::new(),
+            beacons: BTreeMap::new(),
+            lagrange_cache: LagrangeCache::new(LAGRANGE_CACHE_CAPACITY),
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum RewardSource {
+    /// A partial signature taken into a fulfilled signature task.
+    Signature,
+    /// Committing the signature of a task.
+    Committer,
+    /// Triggering a task on behalf of the network, which the mock doesn't pay for yet.
+    Trigger,
+    /// Proving a committer submitted an invalid partial signature.
+    Challenge,
+}
+
+/// How a node took part in the work of its groups.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, Serialize)]
+pub struct ParticipationStats {
+    /// Partial signatures taken into fulfilled tasks.
+    pub partial_signatures: usize,
+    /// Fulfilled tasks the node was eligible for, without its partial signature.
+    pub missed_tasks: usize,
+    /// DKG results the node committed.
+    pub dkg_commits: usize,
+}
+
+impl ParticipationStats {
+    /// The percentage of the tasks the node signed, 100 until it was eligible for one.
+    pub fn signing_rate(&self) -> usize {
+        let tasks = self.partial_signatures + self.missed_tasks;
+
+        if tasks == 0 {
+            return 100;
+        }
+
+        self.partial_signatures * 100 / tasks
+    }
+}
+
+/// The accounting of an earner: what it earned, claimed and got slashed, with its
+/// earnings broken down by the epoch of the group they were earned in and by source.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Serialize)]
+pub struct RewardStatement {
+    pub earned: usize,
+    pub claimed: usize,
+    pub slashed: usize,
+    pub earned_by_epoch: BTreeMap<(Epoch, RewardSource), usize>,
+}
+
+impl RewardStatement {
+    pub fn earned_from(&self, source: RewardSource) -> usize {
+        self.earned_by_epoch
+            .iter()
+            .filter(|((_, s), _)| *s == source)
+            .map(|(_, amount)| amount)
+            .sum()
+    }
+}
+
+#[derive(Clone)]
 pub struct Node {
     pub id_address: String,
     pub id_public_key: Vec<u8>,
@@ -60,27 +283,151 @@ pub struct Node {
     pub state: bool,
     pub pending_until_block: usize,
     pub staking: usize,
+    pub capabilities: Capabilities,
+    // the last block the node sent a heartbeat, a commitment or a signature at
+    pub last_active_block: usize,
+}
+
+/// Where a group is in its lifecycle. Only active groups sign.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupState {
+    /// Gathering members, too few to run a DKG
+    Forming,
+    /// A DKG was emitted for the current epoch and no key reached consensus yet
+    AwaitingCommits,
+    /// Holds a key enough members can sign with, possibly while re-keying
+    Active,
+    /// Had a key but lost too many members to reach its threshold
+    Degraded,
+    /// Disbanded by the owner, until members join again
+    Disbanded,
+}
+
+impl GroupState {
+    /// Whether a group may move from this state to `next`.
+    pub fn can_become(self, next: GroupState) -> bool {
+        use GroupState::*;
+
+        self == next
+            || match (self, next) {
+                (_, Disbanded) => true,
+                (Forming, AwaitingCommits) => true,
+                (AwaitingCommits, Active) | (AwaitingCommits, Forming) => true,
+                // a failed re-keying leaves the group waiting for the next DKG
+                (Active, AwaitingCommits) | (Active, Degraded) => true,
+                (Degraded, AwaitingCommits) => true,
+                (Disbanded, Forming) => true,
+                _ => false,
+            }
+    }
 }
 
 #[derive(Clone)]
 pub struct Group {
-    pub index: usize,
-    pub epoch: usize,
+    pub index: GroupIndex,
+    pub epoch: Epoch,
     pub capacity: usize,
+    pub minimum_threshold: usize,
     pub size: usize,
     pub threshold: usize,
-    pub state: bool,
+    /// The threshold the current key was generated with, 0 until the group has a key.
+    /// Members joining or leaving move `threshold` for the next DKG but not this one.
+    pub key_threshold: usize,
+    pub state: GroupState,
     pub public_key: Vec<u8>,
-    pub members: HashMap<String, Member>,
+    pub members: BTreeMap<String, Member>,
     pub committers: Vec<String>,
-    pub commit_cache: HashMap<String, CommitCache>,
+    pub commit_cache: BTreeMap<String, CommitCache>,
+    /// The epoch of the DKG the group is waiting on, None once its key is committed.
+    /// An active group keeps signing with its previous key meanwhile.
+    pub pending_epoch: Option<Epoch>,
+    /// Emptied by scattering its members, it takes no joiners anymore or it would get
+    /// all of them as the smallest group. New groups are created instead.
+    pub scattered: bool,
+}
+
+impl Group {
+    /// Hashes the group with its members and commitments in key order, so that a
+    /// snapshot cached by a node hashes the same as the group held by the controller.
+    /// The group is hashed in its bincode encoding with the controller's mixing
+    /// function, so the hash doesn't depend on the platform or Rust version.
+    pub fn state_hash(&self, mixing: MixingFunction) -> u64 {
+        let members = self
+            .members
+            .iter()
+            .map(|(id_address, member)| {
+                (
+                    id_address,
+                    member.index,
+                    &member.partial_public_key,
+                    member.share_index,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let commit_cache = self
+            .commit_cache
+            .iter()
+            .map(|(id_address, cache)| {
+                (id_address, &cache.commit_result, &cache.partial_public_key)
+            })
+            .collect::<Vec<_>>();
+
+        mixing.mix(&(
+            self.index,
+            self.epoch,
+            self.capacity,
+            self.minimum_threshold,
+            self.size,
+            self.threshold,
+            self.key_threshold,
+            self.state,
+            &self.public_key,
+            &self.committers,
+            members,
+            commit_cache,
+            self.pending_epoch,
+            self.scattered,
+        ))
+    }
+
+    /// Sets the threshold for the current size, never below the minimum of the group.
+    fn update_threshold(&mut self) {
+        self.threshold = max(self.minimum_threshold, minimum_threshold(self.size));
+    }
+
+    fn set_state(&mut self, next: GroupState) {
+        assert!(
+            self.state.can_become(next),
+            "group {} can't go from {:?} to {:?}",
+            self.index,
+            self.state,
+            next
+        );
+
+        self.state = next;
+    }
+
+    /// Goes back to waiting for members, degraded if the group had a key.
+    fn fall_back(&mut self) {
+        self.pending_epoch = None;
+
+        match self.state {
+            GroupState::Active | GroupState::Degraded => self.set_state(GroupState::Degraded),
+            _ => self.set_state(GroupState::Forming),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct Member {
-    index: usize,
+    index: MemberIndex,
     id_address: String,
     partial_public_key: Vec<u8>,
+    // the index of the member's share of the group key, which stays put when the member
+    // index gets compacted, None until a key including the member is committed
+    share_index: Option<Idx>,
 }
 
 #[derive(Clone)]
@@ -89,90 +436,541 @@ pub struct CommitCache {
     partial_public_key: Vec<u8>,
 }
 
-#[derive(Hash, Clone)]
+#[derive(Hash, Clone, Serialize)]
 pub struct CommitResult {
-    group_epoch: usize,
+    group_epoch: Epoch,
     public_key: Vec<u8>,
     disqualified_nodes: Vec<String>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Hash, Serialize)]
 pub struct SignatureTask {
-    pub index: usize,
-    pub message: String,
-    pub group_index: usize,
+    pub index: TaskIndex,
+    // what the requester asked to be signed, the chained beacon message for beacon rounds
+    pub seed: Vec<u8>,
+    // the controller itself for beacon rounds
+    pub requester: String,
+    pub group_index: GroupIndex,
+    // the block height of the request, which the message is bound to
+    pub request_block_height: usize,
+    // when the task was last assigned, moved when it is reassigned to another group
     pub assignment_block_height: usize,
+    // the round signed by the task if it was emitted in beacon mode
+    pub beacon_round: Option<usize>,
+    // what the requester paid, nothing for beacon rounds
+    pub fee: usize,
+    // the members of the group when the task was assigned, who get paid for it
+    // even if they leave the group before it is fulfilled
+    pub eligible_members: BTreeMap<String, EligibleMember>,
+}
+
+impl SignatureTask {
+    /// What the group signs. Beacon rounds sign their chained message as is, so that the
+    /// chain can be checked from the signatures alone.
+    pub fn message(&self) -> Vec<u8> {
+        match self.beacon_round {
+            Some(_) => self.seed.clone(),
+            None => request_message(&self.seed, &self.requester, self.request_block_height),
+        }
+    }
+}
+
+#[derive(Clone, Hash, Debug, PartialEq, Serialize)]
+pub struct EligibleMember {
+    pub reward_address: String,
+    pub partial_public_key: Vec<u8>,
+    /// The index its partial signatures must carry, None if it holds no share of the key.
+    pub share_index: Option<Idx>,
+}
+
+/// What a node that was offline when a DKG task was emitted should do about it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DKGCatchUp {
+    /// The shares phase is still open, the node runs the DKG like everyone else
+    Join,
+    /// The node missed the shares phase. It waits for the ceremony to end and get
+    /// archived through check_dkg_state, the group is given a new task if it needs one
+    AwaitCheckDkgState,
 }
 
 #[derive(Clone)]
 pub struct DKGTask {
-    pub group_index: usize,
-    pub epoch: usize,
+    pub index: usize,
+    pub group_index: GroupIndex,
+    pub epoch: Epoch,
     pub size: usize,
     pub threshold: usize,
-    pub members: HashMap<String, usize>,
+    pub members: BTreeMap<String, MemberIndex>,
     pub assignment_block_height: usize,
 }
 
+#[derive(Clone)]
 pub struct SignatureReward {
     signature_task: SignatureTask,
     committer: String,
-    group: Group,
+    group_epoch: Epoch,
     partial_signatures: HashMap<String, Vec<u8>>,
 }
 
+/// Emitted whenever a signature reward is challenged, so that the committer learns about
+/// it and can check the challenged partial signatures on its own.
+#[derive(Clone)]
+pub struct ChallengeEvent {
+    pub signature_index: TaskIndex,
+    pub challenger: String,
+    pub committer: String,
+    pub signature_task: SignatureTask,
+    pub partial_signatures: HashMap<String, Vec<u8>>,
+    // the member whose partial signature didn't verify, None if the challenge failed
+    pub invalid_partial_signer: Option<String>,
+}
+
+/// A broken invariant of the controller state, which no sequence of transactions should cause.
+#[derive(Debug, Error, PartialEq)]
+pub enum InvariantViolation {
+    #[error("group {0} lists {1} as a member, which is not a registered node")]
+    UnknownMember(GroupIndex, String),
+    #[error("{1} is a member of group {0} while frozen")]
+    FrozenMember(GroupIndex, String),
+    #[error("{0} is a member of both group {1} and group {2}")]
+    MemberOfSeveralGroups(String, GroupIndex, GroupIndex),
+    #[error("group {0} has a size of {1} but {2} members")]
+    SizeMismatch(GroupIndex, usize, usize),
+    #[error("group {0} is valid with {1} members, below its threshold of {2}")]
+    ValidBelowThreshold(GroupIndex, usize, usize),
+    #[error("{1} is a committer of group {0} but not a member of it")]
+    CommitterNotAMember(GroupIndex, String),
+    #[error("signature task {0} is assigned to group {1}, which does not exist")]
+    UnknownAssignedGroup(TaskIndex, GroupIndex),
+    #[error("the member indices of group {0} are not 1 to its size")]
+    NonContiguousMemberIndices(GroupIndex),
+}
+
+/// Why a fulfillment is rejected.
+#[derive(Debug, Error, PartialEq)]
+pub enum FulfillmentError {
+    #[error("{0} partial signatures were submitted for a group of {1}")]
+    TooManyPartialSignatures(usize, usize),
+    #[error("the signature is {0} bytes long, expected {1}")]
+    InvalidSignatureLength(usize, usize),
+    #[error("the partial signature of {0} is {1} bytes long, expected {2}")]
+    InvalidPartialSignatureLength(String, usize, usize),
+    #[error("{0} partial signatures were submitted, the threshold is {1}")]
+    NotEnoughPartialSignatures(usize, usize),
+    #[error("{0} is not a member of the group")]
+    NotAMember(String),
+    #[error("the partial signature of {0} is invalid")]
+    InvalidPartialSignature(String),
+    #[error("the partial signature of {0} carries index {1}, which is not its share's")]
+    ShareIndexMismatch(String, Idx),
+    #[error("the partial signatures don't aggregate to the signature")]
+    SignatureMismatch,
+}
+
+/// Why a node refuses to commit the output of its DKG.
+#[derive(Debug, Error, PartialEq)]
+pub enum DKGOutputError {
+    #[error("the task's threshold is {0} but the DKG ran with {1}")]
+    ThresholdMismatch(usize, usize),
+    #[error("the DKG qualified index {0}, which is not a member of the task")]
+    UnknownQualifiedIndex(Idx),
+}
+
+/// Checks the task the controller emitted against the DKG run for it, before committing
+/// its output: the thresholds must be equal and every qualified DKG index must belong to
+/// a member of the task.
+pub fn check_dkg_output(
+    task: &DKGTask,
+    threshold: usize,
+    qualified: impl IntoIterator<Item = Idx>,
+) -> Result<(), DKGOutputError> {
+    if task.threshold != threshold {
+        return Err(DKGOutputError::ThresholdMismatch(task.threshold, threshold));
+    }
+
+    let member_indices = task
+        .members
+        .values()
+        .map(|member_index| member_index.dkg_index())
+        .collect::<BTreeSet<_>>();
+
+    match qualified
+        .into_iter()
+        .find(|index| !member_indices.contains(index))
+    {
+        Some(index) => Err(DKGOutputError::UnknownQualifiedIndex(index)),
+        None => Ok(()),
+    }
+}
+
+/// What a request for `num_words` random words costs.
+pub fn request_fee(num_words: usize) -> usize {
+    REQUEST_FEE + REQUEST_FEE_PER_WORD * num_words
+}
+
+/// The length of a serialized signature, which is a point on G2.
+pub fn signature_length() -> usize {
+    bincode::serialized_size(&G2::one()).unwrap() as usize
+}
+
+/// The length of a serialized partial signature, a signature along with the index of its signer.
+pub fn partial_signature_length() -> usize {
+    let partial = Eval {
+        value: vec![0u8; signature_length()],
+        index: 0,
+    };
+
+    bincode::serialized_size(&partial).unwrap() as usize
+}
+
+/// Checks that a fulfillment is no larger than the group it is submitted for could produce.
+pub fn check_fulfillment_sizes(
+    group_size: usize,
+    signature: &[u8],
+    partial_signatures: &HashMap<String, Vec<u8>>,
+) -> Result<(), FulfillmentError> {
+    if partial_signatures.len() > group_size {
+        return Err(FulfillmentError::TooManyPartialSignatures(
+            partial_signatures.len(),
+            group_size,
+        ));
+    }
+
+    if signature.len() != signature_length() {
+        return Err(FulfillmentError::InvalidSignatureLength(
+            signature.len(),
+            signature_length(),
+        ));
+    }
+
+    let expected = partial_signature_length();
+
+    for (id_address, partial_signature) in partial_signatures.iter() {
+        if partial_signature.len() != expected {
+            return Err(FulfillmentError::InvalidPartialSignatureLength(
+                id_address.clone(),
+                partial_signature.len(),
+                expected,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the partial signatures come from at least `threshold` eligible members,
+/// each one under the index of its share, and that they aggregate to the signature.
+///
+/// Only the `threshold` partial signatures with the lowest indices go into the aggregate,
+/// the others aren't verified here. A committer rewarding an invalid one can be proven
+/// at fault through `challenge_reward`.
+pub fn check_partial_signatures(
+    eligible_members: &BTreeMap<String, EligibleMember>,
+    threshold: usize,
+    signature: &[u8],
+    partial_signatures: &HashMap<String, Vec<u8>>,
+    lagrange_cache: &mut LagrangeCache<<SigScheme as Scheme>::Private>,
+) -> Result<(), FulfillmentError> {
+    // the map is keyed by address, so padding the list with the same member is not possible
+    if partial_signatures.len() < threshold {
+        return Err(FulfillmentError::NotEnoughPartialSignatures(
+            partial_signatures.len(),
+            threshold,
+        ));
+    }
+
+    let partial_signatures = partial_signatures.iter().collect::<BTreeMap<_, _>>();
+
+    for (id_address, partial_signature) in partial_signatures.iter() {
+        let member = match eligible_members.get(*id_address) {
+            Some(member) => member,
+            None => return Err(FulfillmentError::NotAMember((*id_address).clone())),
+        };
+
+        let partial = match bincode::deserialize::<Eval<Vec<u8>>>(partial_signature) {
+            Ok(partial) => partial,
+            Err(_) => {
+                return Err(FulfillmentError::InvalidPartialSignature(
+                    (*id_address).clone(),
+                ))
+            }
+        };
+
+        // or a committer could pass a partial signature off as another share's
+        if member.share_index != Some(partial.index) {
+            return Err(FulfillmentError::ShareIndexMismatch(
+                (*id_address).clone(),
+                partial.index,
+            ));
+        }
+    }
+
+    let partials = partial_signatures
+        .values()
+        .map(|partial_signature| (*partial_signature).clone())
+        .collect::<Vec<_>>();
+
+    match SigScheme::aggregate_cached(threshold, &partials, lagrange_cache) {
+        Ok(aggregated) if aggregated == signature => Ok(()),
+        _ => Err(FulfillmentError::SignatureMismatch),
+    }
+}
+
+/// Returns the first signer, in address order, whose partial signature doesn't verify or
+/// who is not an eligible member. All partial signatures are verified together first, so
+/// a valid set only costs one pairing check.
+pub fn find_invalid_partial_signer(
+    eligible_members: &BTreeMap<String, EligibleMember>,
+    message: &[u8],
+    partial_signatures: &HashMap<String, Vec<u8>>,
+) -> Option<String> {
+    let partial_signatures = partial_signatures.iter().collect::<BTreeMap<_, _>>();
+
+    if verify_partial_signatures_at_once(eligible_members, message, &partial_signatures) {
+        return None;
+    }
+
+    partial_signatures
+        .into_iter()
+        .find(
+            |(id_address, partial_signature)| match eligible_members.get(*id_address) {
+                Some(member) => !verify_partial_signature(member, message, partial_signature),
+                None => true,
+            },
+        )
+        .map(|(id_address, _)| id_address.clone())
+}
+
+fn verify_partial_signatures_at_once(
+    eligible_members: &BTreeMap<String, EligibleMember>,
+    message: &[u8],
+    partial_signatures: &BTreeMap<&String, &Vec<u8>>,
+) -> bool {
+    let mut public_keys = Vec::with_capacity(partial_signatures.len());
+
+    let mut signatures = Vec::with_capacity(partial_signatures.len());
+
+    for (id_address, partial_signature) in partial_signatures.iter() {
+        let member = match eligible_members.get(*id_address) {
+            Some(member) => member,
+            None => return false,
+        };
+
+        let partial = match bincode::deserialize::<Eval<Vec<u8>>>(partial_signature) {
+            Ok(partial) if member.share_index == Some(partial.index) => partial,
+            _ => return false,
+        };
+
+        let public_key = match bincode::deserialize::<G1>(&member.partial_public_key) {
+            Ok(public_key) => public_key,
+            Err(_) => return false,
+        };
+
+        public_keys.push(public_key);
+
+        signatures.push(partial.value);
+    }
+
+    let signatures = signatures.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+    SigScheme::aggregation_verify_on_the_same_msg(&public_keys, message, &signatures).is_ok()
+}
+
+/// Verifies the signature wrapped in the partial signature against the partial public
+/// key of the member, and that it carries the index of the member's share. Nodes can run
+/// it on the data of a challenge event.
+pub fn verify_partial_signature(
+    member: &EligibleMember,
+    message: &[u8],
+    partial_signature: &[u8],
+) -> bool {
+    let partial = match bincode::deserialize::<Eval<Vec<u8>>>(partial_signature) {
+        Ok(partial) if member.share_index == Some(partial.index) => partial,
+        _ => return false,
+    };
+
+    let public_key = match bincode::deserialize::<G1>(&member.partial_public_key) {
+        Ok(public_key) => public_key,
+        Err(_) => return false,
+    };
+
+    SigScheme::verify(&public_key, message, &partial.value).is_ok()
+}
+
 trait Internal {
+    fn node_join(&mut self, id_address: &str);
+
+    fn find_or_create_available_group(&mut self) -> GroupIndex;
+
+    fn add_group(&mut self, config: GroupConfig) -> GroupIndex;
+
+    fn add_to_group(
+        &mut self,
+        id_address: &str,
+        group_index: GroupIndex,
+        emit_event_instantly: bool,
+    );
+
+    fn remove_from_group(
+        &mut self,
+        id_address: &str,
+        group_index: GroupIndex,
+        emit_event_instantly: bool,
+    );
+
+    fn rebalance_or_escalate(&mut self, group_index: GroupIndex);
+
+    fn apply_rebalance_plan(&mut self, plan: &[Move]);
+
+    fn emit_group_event(&mut self, group_index: GroupIndex);
+
+    fn group_members(&self) -> BTreeMap<GroupIndex, Vec<String>>;
+
+    fn choose_committers(&self, group_index: GroupIndex) -> Vec<String>;
+
     fn freeze_node(&mut self, id_address: &str, pending_until_block: usize);
 
-    fn calculate_hash<T: Hash>(t: &T) -> u64;
+    fn next_valid_group_index(&mut self) -> Option<GroupIndex>;
+
+    fn eligible_members(&self, group_index: GroupIndex) -> BTreeMap<String, EligibleMember>;
+
+    /// Pays `amount` to the reward address and records it on the earner's statement.
+    fn accrue_reward(
+        &mut self,
+        id_address: &str,
+        reward_address: &str,
+        source: RewardSource,
+        group_epoch: Epoch,
+        amount: usize,
+    );
+
+    fn archive_coordinator(&mut self, coordinator: Coordinator);
+
+    fn emit_beacon_task(&mut self);
+
+    /// Runs the DKG again for the groups whose commits missed the deadline, re-keying
+    /// active groups included, without the members that didn't commit. Those are
+    /// slashed and frozen like a committer caught by a challenge.
+    fn sweep_stuck_groups(&mut self);
+
+    /// Takes `amount` from the node's stake and freezes it.
+    fn slash_node(&mut self, id_address: &str, amount: usize);
+
+    fn record_activity(&mut self, id_address: &str);
+
+    /// Freezes the active nodes that showed no activity within the liveness timeout,
+    /// which regroups their groups.
+    fn sweep_unresponsive_nodes(&mut self);
 }
 
 pub trait MockHelper {
-    fn emit_dkg_task(&self) -> &DKGTask;
+    /// Returns all DKG tasks emitted since `from_index`, in emission order.
+    fn emit_dkg_task(&self, from_index: usize) -> Vec<&DKGTask>;
 
-    fn emit_signature_task(&self) -> &SignatureTask;
+    /// Same as `emit_dkg_task` but only returns the tasks of groups the node is a member of.
+    fn emit_dkg_task_for_node(&self, id_address: &str, from_index: usize) -> Vec<&DKGTask>;
+
+    /// The DKG tasks a node coming back from downtime still has to act on, whatever it
+    /// had cached: the latest task of each of its groups, while the group is at that
+    /// epoch, its ceremony is pending and the node hasn't committed. In group order.
+    fn backfill_dkg_tasks(&self, id_address: &str) -> Vec<(&DKGTask, DKGCatchUp)>;
+
+    /// Returns the unfulfilled signature tasks whose index is at least `from_index`,
+    /// in request order.
+    fn emit_signature_task(&self, from_index: TaskIndex) -> Vec<&SignatureTask>;
+
+    /// Returns all challenge events emitted since `from_index`, in emission order.
+    fn emit_challenge_event(&self, from_index: usize) -> Vec<&ChallengeEvent>;
+
+    /// Stands in for calling the coordinator contract of the group directly.
+    fn get_coordinator_mut(&mut self, group_index: GroupIndex) -> Option<&mut Coordinator>;
 
     fn mine(&mut self, block_number: usize);
 }
 
 pub trait Transactions {
+    /// Fails if the capabilities of the node don't pass `Capabilities::check` against
+    /// the scheme of the controller, so that groups never mix incompatible nodes.
     fn node_register(
         &mut self,
         id_address: String,
         id_public_key: Vec<u8>,
         endpoint: String,
         reward_address: String,
+        capabilities: Capabilities,
+    ) -> bool;
+
+    /// Replaces the id key of the node. `signature` is made with the current key and
+    /// `proof_of_possession` with the new one, both over `node_key_update_message`.
+    /// Every group of the node runs a new DKG with the new key.
+    fn update_node_key(
+        &mut self,
+        id_address: String,
+        id_public_key: Vec<u8>,
+        signature: Vec<u8>,
+        proof_of_possession: Vec<u8>,
     ) -> bool;
 
     fn node_quit(&mut self, id_address: String);
 
-    fn node_activate(&mut self, id_address: String);
+    /// Brings a frozen node back once its pending period is over, topping its stake back
+    /// up from its balance, which it approves the controller for beforehand. Fails for
+    /// blacklisted nodes.
+    fn node_activate(&mut self, id_address: String) -> bool;
 
     fn redeem(&mut self, id_address: String);
 
-    fn claim(&mut self, id_address: String);
+    /// Transfers the rewards accrued at the address from the controller's balance.
+    fn claim(&mut self, id_address: String) -> bool;
 
     fn commit_dkg(
         &mut self,
         id_address: String,
-        group_index: usize,
-        group_epoch: usize,
+        group_index: GroupIndex,
+        group_epoch: Epoch,
         public_key: Vec<u8>,
         partial_public_key: Vec<u8>,
         disqualified_nodes: Vec<String>,
     ) -> bool;
 
-    fn request(&mut self, message: String) -> bool;
+    /// Assigns a task signing the seed, along with the requester and the current block
+    /// height, to the next group. The requester pays the fee.
+    fn request(&mut self, requester: String, seed: Vec<u8>, num_words: usize) -> bool;
 
     fn fulfill(
         &mut self,
         id_address: String,
-        signature_index: usize,
+        signature_index: TaskIndex,
         signature: Vec<u8>,
         partial_signatures: HashMap<String, Vec<u8>>,
     ) -> bool;
 
-    fn challenge_reward(&mut self, id_address: String, signature_index: usize) -> bool;
+    fn challenge_reward(&mut self, id_address: String, signature_index: TaskIndex) -> bool;
+
+    /// Owner only. Deactivates the group and sends its members back to the join pool,
+    /// after which they get regrouped and a new DKG task is emitted.
+    fn disband_group(&mut self, id_address: String, group_index: GroupIndex) -> bool;
+
+    /// Moves the coordinator of the group to the archive once its DKG has ended.
+    fn check_dkg_state(&mut self, id_address: String, group_index: GroupIndex) -> bool;
+
+    /// Owner only. Sets the sizing of the groups created from now on, existing groups
+    /// keep theirs. Fails if the config is invalid.
+    fn set_group_config(&mut self, id_address: String, config: GroupConfig) -> bool;
+
+    /// Owner only. Creates an empty group with its own sizing, which nodes then join
+    /// like any other group.
+    fn create_group(&mut self, id_address: String, config: GroupConfig) -> bool;
+
+    /// Owner only. Keeps the node from registering, and freezes it out of its groups
+    /// if it is registered already. Fails if it is blacklisted already.
+    fn blacklist_node(&mut self, id_address: String, node: String) -> bool;
+
+    /// Owner only. Lifts the ban, a frozen node stays frozen until it activates again.
+    fn unblacklist_node(&mut self, id_address: String, node: String) -> bool;
+
+    /// Tells the controller an active node is alive when it had nothing else to do.
+    fn node_heartbeat(&mut self, id_address: String) -> bool;
 }
 
 pub trait Views {
@@ -180,509 +978,3927 @@ pub trait Views {
 
     fn get_node(&self, id_address: String) -> &Node;
 
-    fn get_group(&self, index: usize) -> &Group;
+    fn get_group(&self, index: GroupIndex) -> &Group;
 
-    fn valid_group_indices(&self) -> Vec<usize>;
+    /// Same as `get_group` but None if there is no such group.
+    fn find_group(&self, index: GroupIndex) -> Option<&Group>;
 
-    fn pending_signature_tasks(&self) -> Vec<&SignatureTask>;
+    /// None if there is no such group.
+    fn get_group_state(&self, index: GroupIndex) -> Option<GroupState>;
 
-    fn verifiable_signature_rewards(&self) -> Vec<&SignatureReward>;
-}
+    /// The blacklisted addresses, in order.
+    fn get_blacklisted_nodes(&self) -> Vec<&String>;
 
-impl Internal for Controller {
-    fn freeze_node(&mut self, id_address: &str, pending_until_block: usize) {
-        let node = self.nodes.get_mut(id_address).unwrap();
-        node.state = false;
-        node.pending_until_block = pending_until_block;
-        // regroup which this node belongs to
-        todo!()
-    }
+    /// None if the node never was eligible for a task nor committed a DKG result.
+    fn get_participation_stats(&self, id_address: &str) -> Option<&ParticipationStats>;
 
-    fn calculate_hash<T: Hash>(t: &T) -> u64 {
-        let mut s = DefaultHasher::new();
-        t.hash(&mut s);
-        s.finish()
-    }
-}
+    fn valid_group_indices(&self) -> Vec<GroupIndex>;
 
-impl MockHelper for Controller {
-    fn emit_dkg_task(&self) -> &DKGTask {
-        &self.dkg_task.as_ref().unwrap()
-    }
+    fn pending_signature_tasks(&self) -> Vec<&SignatureTask>;
 
-    fn emit_signature_task(&self) -> &SignatureTask {
-        &self.signature_task.as_ref().unwrap()
-    }
+    fn verifiable_signature_rewards(&self) -> Vec<&SignatureReward>;
 
-    fn mine(&mut self, block_number: usize) {
-        self.block_height += block_number;
-    }
-}
+    fn get_coordinator(&self, group_index: GroupIndex) -> Option<&Coordinator>;
 
-impl Transactions for Controller {
-    fn node_register(
-        &mut self,
+    fn get_archived_coordinator(
+        &self,
+        group_index: GroupIndex,
+        epoch: Epoch,
+    ) -> Option<&Coordinator>;
+
+    /// Returns the shares, responses and justifications published during the DKG
+    /// of the group at `epoch`, for auditing a ceremony after the fact.
+    fn get_dkg_transcript(&self, group_index: GroupIndex, epoch: Epoch) -> Option<DKGTranscript>;
+
+    /// Runs `commit_dkg` on a copy of the controller and returns what it would return,
+    /// so that a node can check its commitment without changing any state.
+    fn simulate_commit_dkg(
+        &self,
         id_address: String,
-        id_public_key: Vec<u8>,
-        endpoint: String,
-        reward_address: String,
-    ) -> bool {
-        if self.nodes.contains_key(&id_address) {
-            return false;
-        }
+        group_index: GroupIndex,
+        group_epoch: Epoch,
+        public_key: Vec<u8>,
+        partial_public_key: Vec<u8>,
+        disqualified_nodes: Vec<String>,
+    ) -> bool;
 
-        // mock: staking
+    /// Same as `simulate_commit_dkg` for `fulfill`, which lets a committer check its
+    /// submission before racing the other committers.
+    fn simulate_fulfill(
+        &self,
+        id_address: String,
+        signature_index: TaskIndex,
+        signature: Vec<u8>,
+        partial_signatures: HashMap<String, Vec<u8>>,
+    ) -> bool;
 
-        let node = Node {
-            id_address: id_address.clone(),
-            id_public_key,
-            endpoint,
-            reward_address,
-            state: true,
-            pending_until_block: 0,
-            staking: 50000,
-        };
+    /// Hashes groups, nodes, rewards, tasks and coordinators, each map in key order,
+    /// so that two controllers that went through the same transactions hash the same.
+    /// The state is hashed in its bincode encoding, where every usize is a u64, with the
+    /// mixing function, so nodes on different platforms and toolchains can compare hashes.
+    fn state_hash(&self) -> u64;
 
-        self.nodes.insert(id_address.clone(), node);
+    /// Lets a node check its cached snapshot of the group against `Group::state_hash`.
+    fn group_state_hash(&self, group_index: GroupIndex) -> Option<u64>;
 
-        self.rewards.insert(id_address.clone(), 0);
+    /// Returns the partial public keys committed by the members of the group, so that a
+    /// committer that missed the commit phase can verify their partial signatures. None
+    /// unless the group is active at `epoch`.
+    fn get_group_member_keys(
+        &self,
+        group_index: GroupIndex,
+        epoch: Epoch,
+    ) -> Option<BTreeMap<String, Vec<u8>>>;
 
-        // TODO: now supports single group only
-        if self.groups.is_empty() {
-            let group = Group {
-                index: 1,
-                epoch: 0,
-                capacity: 10,
-                size: 0,
-                threshold: DEFAULT_MINIMUM_THRESHOLD,
-                state: false,
-                public_key: vec![],
-                members: HashMap::new(),
-                committers: vec![],
-                commit_cache: HashMap::new(),
-            };
-            self.groups.insert(1, group);
-        }
+    fn get_beacon(&self, round: usize) -> Option<&Vec<u8>>;
 
-        let group = self.groups.get_mut(&1).unwrap();
+    /// The protocol's share of the request fees collected so far.
+    fn get_treasury(&self) -> usize;
 
-        group.size += 1;
+    /// Returns what the node earned, claimed and got slashed, None if it never did any.
+    fn get_reward_statement(&self, id_address: &str) -> Option<&RewardStatement>;
 
-        let member = Member {
-            index: group.size,
-            id_address: id_address.clone(),
-            partial_public_key: vec![],
-        };
+    /// The last round whose beacon was signed, 0 if there is none yet.
+    fn latest_beacon_round(&self) -> usize;
 
-        group.members.insert(id_address, member);
+    /// Checks the invariants of groups and tasks that every transaction must preserve.
+    fn check_invariants(&self) -> Result<(), InvariantViolation>;
 
-        let minimum = minimum_threshold(group.size);
+    /// The sizing of newly created groups. That of an existing group is on the group.
+    fn get_group_config(&self) -> &GroupConfig;
+}
 
-        group.threshold = max(DEFAULT_MINIMUM_THRESHOLD, minimum);
+impl Internal for Controller {
+    fn node_join(&mut self, id_address: &str) {
+        let group_index = self.find_or_create_available_group();
 
-        if group.size >= 3 {
-            group.epoch += 1;
+        self.add_to_group(id_address, group_index, true);
 
-            let mut members = HashMap::new();
+        self.rebalance_or_escalate(group_index);
+    }
 
-            for (member_id_address, member) in group.members.iter() {
-                members.insert(member_id_address.clone(), member.index.clone());
+    fn find_or_create_available_group(&mut self) -> GroupIndex {
+        let candidates = self
+            .groups
+            .values()
+            .filter(|group| group.size < group.capacity && !group.scattered)
+            .collect::<Vec<_>>();
+
+        let available_group = self
+            .grouping
+            .strategy()
+            .choose_group(&candidates, &self.nodes);
+
+        let valid_group_count = self.valid_group_indices().len();
+
+        match available_group {
+            Some(group_index)
+                if valid_group_count >= IDEAL_NUMBER_OF_GROUPS
+                    || valid_group_count != self.groups.len() =>
+            {
+                group_index
             }
+            _ => self.add_group(self.group_config),
+        }
+    }
 
-            let dkg_task = DKGTask {
-                group_index: group.index,
-                epoch: group.epoch,
-                size: group.size,
-                threshold: group.threshold,
-                members,
-                assignment_block_height: self.block_height,
-            };
+    fn add_group(&mut self, config: GroupConfig) -> GroupIndex {
+        let group_index = GroupIndex(self.groups.len() + 1);
+
+        let group = Group {
+            index: group_index,
+            epoch: Epoch(0),
+            capacity: config.capacity,
+            minimum_threshold: config.minimum_threshold,
+            size: 0,
+            threshold: config.minimum_threshold,
+            key_threshold: 0,
+            state: GroupState::Forming,
+            public_key: vec![],
+            members: BTreeMap::new(),
+            committers: vec![],
+            commit_cache: BTreeMap::new(),
+            pending_epoch: None,
+            scattered: false,
+        };
 
-            self.dkg_task = Some(dkg_task);
-            // self.emit_dkg_task(dkg_task);
-        }
+        self.groups.insert(group_index, group);
 
-        true
+        group_index
     }
 
-    fn node_quit(&mut self, _id_address: String) {
-        todo!()
-    }
+    fn add_to_group(
+        &mut self,
+        id_address: &str,
+        group_index: GroupIndex,
+        emit_event_instantly: bool,
+    ) {
+        let group = self.groups.get_mut(&group_index).unwrap();
 
-    fn node_activate(&mut self, _id_address: String) {
-        todo!()
-    }
+        if group.state == GroupState::Disbanded {
+            group.set_state(GroupState::Forming);
+        }
 
-    fn redeem(&mut self, _id_address: String) {
-        todo!()
-    }
+        // disqualification may have left gaps in the indices of the last DKG
+        let mut members = group.members.values_mut().collect::<Vec<_>>();
 
-    fn claim(&mut self, _id_address: String) {
-        todo!()
-    }
+        members.sort_by_key(|member| member.index);
 
-    fn commit_dkg(
-        &mut self,
-        id_address: String,
-        group_index: usize,
-        group_epoch: usize,
-        public_key: Vec<u8>,
-        partial_public_key: Vec<u8>,
-        disqualified_nodes: Vec<String>,
-    ) -> bool {
-        let group = self.groups.get_mut(&group_index).unwrap();
+        members
+            .into_iter()
+            .enumerate()
+            .for_each(|(i, member)| member.index = MemberIndex(i + 1));
 
-        if !group.members.contains_key(&id_address) || group.epoch != group_epoch {
-            return false;
-        }
+        group.size += 1;
 
-        let commit_result = CommitResult {
-            group_epoch,
-            public_key,
-            disqualified_nodes: disqualified_nodes.clone(),
+        let member = Member {
+            index: MemberIndex(group.size),
+            id_address: id_address.to_string(),
+            partial_public_key: vec![],
+            share_index: None,
         };
 
-        let commit_cache = CommitCache {
-            commit_result,
-            partial_public_key: partial_public_key.clone(),
-        };
+        group.members.insert(id_address.to_string(), member);
 
-        // TODO when next group epoch increments, clean commit_cache, committers
-        if group.commit_cache.contains_key(&id_address) {
-            return false;
+        group.update_threshold();
+
+        if emit_event_instantly {
+            self.emit_group_event(group_index);
         }
+    }
 
-        group.commit_cache.insert(id_address.clone(), commit_cache);
+    fn remove_from_group(
+        &mut self,
+        id_address: &str,
+        group_index: GroupIndex,
+        emit_event_instantly: bool,
+    ) {
+        let group = self.groups.get_mut(&group_index).unwrap();
 
-        fn get_identical_over_threshold_commitment(
-            controller: &Controller,
-            group_index: usize,
-        ) -> Option<CommitCache> {
-            let group = controller.groups.get(&group_index).unwrap();
+        let removed = group.members.remove(id_address).unwrap();
 
-            let mut map: HashMap<u64, usize> = HashMap::new();
+        group.size -= 1;
 
-            for commit_cache in group.commit_cache.values() {
-                let count = map
-                    .entry(Controller::calculate_hash(&commit_cache.commit_result))
-                    .or_insert(0);
+        // keeps member indices contiguous for the next DKG
+        group
+            .members
+            .values_mut()
+            .filter(|member| member.index > removed.index)
+            .for_each(|member| member.index = MemberIndex(member.index.0 - 1));
 
-                *count += 1;
+        group.committers.retain(|committer| committer != id_address);
 
-                if *count >= group.threshold {
-                    return Some(commit_cache.clone());
-                }
-            }
+        group.commit_cache.remove(id_address);
 
-            None
+        // the remaining members can't reach the threshold of the key anymore
+        if group.state == GroupState::Active && group.size < group.key_threshold {
+            group.set_state(GroupState::Degraded);
         }
 
-        if group.state {
-            // it's no good for a qualified node to miscommits here. So far we don't verify this commitment.
-            let member = group.members.get_mut(&id_address).unwrap();
+        group.update_threshold();
+
+        if emit_event_instantly {
+            self.emit_group_event(group_index);
+        }
+    }
 
-            member.partial_public_key = partial_public_key;
-        } else {
-            match get_identical_over_threshold_commitment(self, group_index) {
-                None => {}
-                Some(commit_cache) => {
-                    let group = self.groups.get_mut(&group_index).unwrap();
+    fn rebalance_or_escalate(&mut self, group_index: GroupIndex) {
+        let group = self.groups.get(&group_index).unwrap();
 
-                    group.state = true;
+        if group.size == 0 || group.size >= group.minimum_threshold {
+            return;
+        }
 
-                    group.size -= commit_cache.commit_result.disqualified_nodes.len();
+        // an undersized group can't run a DKG on its own, borrow members from the others
+        let groups = &self.groups;
 
-                    group.public_key = commit_cache.commit_result.public_key.clone();
+        let plan = self.grouping.strategy().plan_rebalance(
+            &self.group_members(),
+            &|index| groups[&index].minimum_threshold,
+            self.last_output,
+            self.mixing,
+        );
 
-                    commit_cache
-                        .commit_result
-                        .disqualified_nodes
-                        .iter()
-                        .for_each(|disqualified_id_address| {
-                            group.members.remove(disqualified_id_address);
-                        });
+        self.apply_rebalance_plan(&plan);
 
-                    for (id_address, cache) in group.commit_cache.iter_mut() {
-                        if !disqualified_nodes.contains(id_address) {
-                            let member = group.members.get_mut(id_address).unwrap();
+        if plan.iter().any(|m| m.to_group_index == group_index) {
+            return;
+        }
 
-                            member.partial_public_key = cache.partial_public_key.clone();
-                        }
-                    }
+        let group = self.groups.get_mut(&group_index).unwrap();
 
-                    // choose 3 committers randomly by last randomness output
+        group.fall_back();
 
-                    let hash1 = Controller::calculate_hash(&self.last_output) as usize;
+        if self.rebalance_failure_policy == RebalanceFailurePolicy::LeavePending {
+            return;
+        }
 
-                    let hash2 = Controller::calculate_hash(&hash1) as usize;
+        let mut members = group.members.values().cloned().collect::<Vec<_>>();
 
-                    let hash3 = Controller::calculate_hash(&hash2) as usize;
+        members.sort_by_key(|member| member.index);
 
-                    let mut index_member_map: HashMap<usize, String> = HashMap::new();
+        let mut touched_group_indices = vec![];
 
-                    group.members.iter().for_each(|(id_address, member)| {
-                        index_member_map.insert(member.index, id_address.clone());
-                    });
+        for member in members {
+            let candidates = self
+                .groups
+                .values()
+                .filter(|group| {
+                    group.index != group_index && group.size < group.capacity && !group.scattered
+                })
+                .collect::<Vec<_>>();
 
-                    let mut qualified_indices = group
-                        .members
-                        .values()
-                        .map(|member| member.index)
-                        .collect::<Vec<_>>();
+            let destination = self
+                .grouping
+                .strategy()
+                .choose_group(&candidates, &self.nodes);
 
-                    let c1 = map_to_qualified_indices(
-                        hash1 % (qualified_indices.len() + 1),
-                        &qualified_indices,
-                    );
+            // nowhere to go, the rest stays pending
+            let destination = match destination {
+                Some(destination) => destination,
+                None => break,
+            };
 
-                    qualified_indices.retain(|&x| x != c1);
+            self.remove_from_group(&member.id_address, group_index, false);
 
-                    let c2 = map_to_qualified_indices(
-                        hash2 % (qualified_indices.len() + 1),
-                        &qualified_indices,
-                    );
+            self.add_to_group(&member.id_address, destination, false);
 
-                    qualified_indices.retain(|&x| x != c2);
+            if !touched_group_indices.contains(&destination) {
+                touched_group_indices.push(destination);
+            }
+        }
 
-                    let c3 = map_to_qualified_indices(
-                        hash3 % (qualified_indices.len() + 1),
-                        &qualified_indices,
-                    );
+        let group = self.groups.get_mut(&group_index).unwrap();
 
-                    group
-                        .committers
-                        .push(index_member_map.get(&c1).unwrap().clone());
+        group.scattered = group.size == 0;
 
-                    group
-                        .committers
-                        .push(index_member_map.get(&c2).unwrap().clone());
+        for group_index in touched_group_indices {
+            self.emit_group_event(group_index);
+        }
+    }
 
-                    group
-                        .committers
-                        .push(index_member_map.get(&c3).unwrap().clone());
+    fn apply_rebalance_plan(&mut self, plan: &[Move]) {
+        let mut touched_group_indices = vec![];
 
-                    fn map_to_qualified_indices(
-                        mut index: usize,
-                        qualified_indices: &[usize],
-                    ) -> usize {
-                        let max = qualified_indices.iter().max().unwrap();
+        for m in plan {
+            self.remove_from_group(&m.id_address, m.from_group_index, false);
 
-                        while !qualified_indices.contains(&index) {
-                            index = (index + 1) % (max + 1);
-                        }
+            self.add_to_group(&m.id_address, m.to_group_index, false);
 
-                        index
-                    }
+            for group_index in [m.from_group_index, m.to_group_index].iter() {
+                if !touched_group_indices.contains(group_index) {
+                    touched_group_indices.push(*group_index);
                 }
             }
         }
 
-        true
+        for group_index in touched_group_indices {
+            self.emit_group_event(group_index);
+        }
     }
 
-    fn request(&mut self, message: String) -> bool {
-        let valid_group_indices = self.valid_group_indices();
+    fn emit_group_event(&mut self, group_index: GroupIndex) {
+        let group = self.groups.get_mut(&group_index).unwrap();
 
-        if valid_group_indices.is_empty() {
-            return false;
+        if group.size < group.minimum_threshold {
+            return;
         }
-        // mock: payment for request
-
-        let mut assignment_group_index = self.last_group_index;
 
-        loop {
-            assignment_group_index = (assignment_group_index + 1) % (self.groups.len() + 1);
+        group.epoch = group.epoch.next();
 
-            if valid_group_indices.contains(&assignment_group_index) {
-                break;
-            }
+        // an active group keeps signing with its key until the new one is committed
+        if group.state != GroupState::Active {
+            group.set_state(GroupState::AwaitingCommits);
         }
 
-        self.signature_count += 1;
+        group.pending_epoch = Some(group.epoch);
 
-        let signature_task = SignatureTask {
-            index: self.signature_count,
-            message,
-            group_index: assignment_group_index,
+        // commitments of the previous epoch can't reach consensus anymore
+        group.commit_cache.clear();
+
+        let mut members = BTreeMap::new();
+
+        for (member_id_address, member) in group.members.iter() {
+            members.insert(member_id_address.clone(), member.index);
+        }
+
+        let dkg_task = DKGTask {
+            index: self.dkg_tasks.len(),
+            group_index: group.index,
+            epoch: group.epoch,
+            size: group.size,
+            threshold: group.threshold,
+            members,
             assignment_block_height: self.block_height,
         };
 
-        self.signature_task = Some(signature_task.clone());
-        // self.emit_signature_task(signature_task.clone());
+        self.dkg_tasks.push(dkg_task);
+        // self.emit_dkg_task(dkg_task);
 
-        self.pending_signature_tasks
-            .insert(signature_task.index, signature_task);
+        let mut participants = group.members.values().collect::<Vec<_>>();
 
-        self.last_group_index = assignment_group_index;
+        participants.sort_by_key(|member| member.index);
 
-        true
+        let nodes = &self.nodes;
+
+        let participants = participants
+            .into_iter()
+            .map(|member| {
+                let node = nodes.get(&member.id_address).unwrap();
+
+                (member.id_address.clone(), node.id_public_key.clone())
+            })
+            .collect::<Vec<_>>();
+
+        let mut coordinator = Coordinator::new(
+            group.index,
+            group.epoch,
+            group.threshold,
+            DEFAULT_PHASE_DURATION,
+        );
+
+        coordinator.initialize(self.block_height, participants);
+
+        if let Some(previous) = self.coordinators.insert(group_index, coordinator) {
+            self.archive_coordinator(previous);
+        }
     }
 
-    fn fulfill(
-        &mut self,
-        id_address: String,
-        signature_index: usize,
-        signature: Vec<u8>,
-        partial_signatures: HashMap<String, Vec<u8>>,
-    ) -> bool {
-        if !self.pending_signature_tasks.contains_key(&signature_index) {
-            return false;
+    fn group_members(&self) -> BTreeMap<GroupIndex, Vec<String>> {
+        self.groups
+            .values()
+            .map(|group| (group.index, group.members.keys().cloned().collect()))
+            .collect()
+    }
+
+    fn choose_committers(&self, group_index: GroupIndex) -> Vec<String> {
+        let group = self.groups.get(&group_index).unwrap();
+
+        // choose up to 3 committers randomly by last randomness output
+
+        let hash1 = self.mixing.mix(&self.last_output) as usize;
+
+        let hash2 = self.mixing.mix(&(hash1 as u64)) as usize;
+
+        let hash3 = self.mixing.mix(&(hash2 as u64)) as usize;
+
+        let mut index_member_map: HashMap<usize, String> = HashMap::new();
+
+        group.members.iter().for_each(|(id_address, member)| {
+            index_member_map.insert(member.index.0, id_address.clone());
+        });
+
+        let mut qualified_indices = group
+            .members
+            .values()
+            .map(|member| member.index.0)
+            .collect::<Vec<_>>();
+
+        // members who sign less often than most don't commit, as long as 3 others can
+        if self.participation_weighting {
+            let participation = &self.participation;
+
+            let signing_rate = |id_address: &String| {
+                participation
+                    .get(id_address)
+                    .map_or(100, |stats| stats.signing_rate())
+            };
+
+            let reliable_indices = group
+                .members
+                .iter()
+                .filter(|(id_address, _)| signing_rate(id_address) >= MIN_COMMITTER_SIGNING_RATE)
+                .map(|(_, member)| member.index.0)
+                .collect::<Vec<_>>();
+
+            if reliable_indices.len() >= 3 {
+                qualified_indices = reliable_indices;
+            }
         }
 
-        let signature_task = self
-            .pending_signature_tasks
-            .get(&signature_index)
-            .unwrap()
-            .clone();
+        fn map_to_qualified_indices(mut index: usize, qualified_indices: &[usize]) -> usize {
+            let max = qualified_indices.iter().max().unwrap();
 
-        let group = self
-            .groups
-            .get(&signature_task.group_index)
-            .unwrap()
-            .clone();
+            while !qualified_indices.contains(&index) {
+                index = (index + 1) % (max + 1);
+            }
 
-        if !group.committers.contains(&id_address) {
-            return false;
+            index
         }
 
-        let message = &signature_task.message;
+        // a group smaller than 3, as its minimum threshold allows, commits with all of them
+        let mut committers = vec![];
+
+        for &hash in [hash1, hash2, hash3].iter() {
+            if qualified_indices.is_empty() {
+                break;
+            }
 
-        let group_public_key: G1 = bincode::deserialize(&group.public_key).unwrap();
+            let committer =
+                map_to_qualified_indices(hash % (qualified_indices.len() + 1), &qualified_indices);
 
-        match SigScheme::verify(&group_public_key, &message.as_bytes(), &signature) {
-            Ok(()) => {}
-            Err(_err) => return false,
+            qualified_indices.retain(|&x| x != committer);
+
+            committers.push(index_member_map.get(&committer).unwrap().clone());
         }
 
-        let committer = self.nodes.get_mut(&id_address).unwrap();
+        committers
+    }
 
-        let committer_address = committer.id_address.clone();
+    fn freeze_node(&mut self, id_address: &str, pending_until_block: usize) {
+        let node = self.nodes.get_mut(id_address).unwrap();
+        node.state = false;
+        node.pending_until_block = pending_until_block;
+        // regroup which this node belongs to
+        let group_indices = self
+            .groups
+            .values()
+            .filter(|group| group.members.contains_key(id_address))
+            .map(|group| group.index)
+            .collect::<BTreeSet<_>>();
 
-        let committer_reward = self.rewards.get_mut(&committer.reward_address).unwrap();
+        for group_index in group_indices {
+            self.remove_from_group(id_address, group_index, true);
+
+            self.rebalance_or_escalate(group_index);
+        }
+    }
 
-        *committer_reward += COMMITTER_REWARD_PER_SIGNATURE;
+    fn next_valid_group_index(&mut self) -> Option<GroupIndex> {
+        let valid_group_indices = self.valid_group_indices();
 
-        partial_signatures.keys().for_each(|member_id_address| {
-            let node = self.nodes.get(member_id_address).unwrap();
+        if valid_group_indices.is_empty() {
+            return None;
+        }
 
-            let member_reward = self.rewards.get_mut(&node.reward_address).unwrap();
+        let mut assignment_group_index = self.last_group_index;
 
-            *member_reward += REWARD_PER_SIGNATURE;
-        });
+        loop {
+            assignment_group_index =
+                GroupIndex((assignment_group_index.0 + 1) % (self.groups.len() + 1));
 
-        self.last_output = Controller::calculate_hash(&signature);
+            if valid_group_indices.contains(&assignment_group_index) {
+                break;
+            }
+        }
 
-        let signature_reward = SignatureReward {
-            signature_task,
-            committer: committer_address,
-            group,
-            partial_signatures,
-        };
+        self.last_group_index = assignment_group_index;
 
-        self.verifiable_signature_rewards
-            .insert(signature_index, signature_reward);
+        Some(assignment_group_index)
+    }
 
-        self.pending_signature_tasks.remove(&signature_index);
+    fn eligible_members(&self, group_index: GroupIndex) -> BTreeMap<String, EligibleMember> {
+        let group = self.groups.get(&group_index).unwrap();
+
+        group
+            .members
+            .iter()
+            .map(|(id_address, member)| {
+                let eligible_member = EligibleMember {
+                    reward_address: self.nodes.get(id_address).unwrap().reward_address.clone(),
+                    partial_public_key: member.partial_public_key.clone(),
+                    share_index: member.share_index,
+                };
+
+                (id_address.clone(), eligible_member)
+            })
+            .collect()
+    }
 
-        true
+    fn accrue_reward(
+        &mut self,
+        id_address: &str,
+        reward_address: &str,
+        source: RewardSource,
+        group_epoch: Epoch,
+        amount: usize,
+    ) {
+        *self.rewards.entry(reward_address.to_string()).or_insert(0) += amount;
+
+        let statement = self
+            .reward_statements
+            .entry(id_address.to_string())
+            .or_default();
+
+        statement.earned += amount;
+
+        *statement
+            .earned_by_epoch
+            .entry((group_epoch, source))
+            .or_insert(0) += amount;
     }
 
-    fn challenge_reward(&mut self, id_address: String, signature_index: usize) -> bool {
-        if !self
-            .verifiable_signature_rewards
-            .contains_key(&signature_index)
-        {
-            return false;
+    fn archive_coordinator(&mut self, coordinator: Coordinator) {
+        self.archived_coordinators.push_back(coordinator);
+
+        while self.archived_coordinators.len() > self.coordinator_retention {
+            self.archived_coordinators.pop_front();
         }
+    }
 
-        let signature_reward = self
-            .verifiable_signature_rewards
-            .get(&signature_index)
-            .unwrap();
+    fn emit_beacon_task(&mut self) {
+        let beacon_period = match self.beacon_period {
+            Some(beacon_period) => beacon_period,
+            None => return,
+        };
+
+        // rounds are chained, so the next one waits for the previous one to be signed
+        if self
+            .pending_signature_tasks
+            .values()
+            .any(|task| task.beacon_round.is_some())
+        {
+            return;
+        }
 
-        let group = &signature_reward.group;
+        let round = self.latest_beacon_round() + 1;
 
-        let committer = self.nodes.get_mut(&signature_reward.committer).unwrap();
+        if self.block_height < round * beacon_period {
+            return;
+        }
 
-        let committer_address = &committer.id_address.clone();
+        let assignment_group_index = match self.next_valid_group_index() {
+            Some(index) => index,
+            None => return,
+        };
 
-        let message = &signature_reward.signature_task.message;
+        self.signature_count += 1;
 
-        // TODO need a BLS-Aggregation Verification instead of loop to save computational fee
-        for (member_id_address, partial_signature) in signature_reward.partial_signatures.iter() {
-            let public_key_as_bytes = &group
-                .members
-                .get(member_id_address)
-                .unwrap()
-                .partial_public_key;
+        let signature_task = SignatureTask {
+            index: TaskIndex(self.signature_count),
+            seed: beacon_message(round, self.get_beacon(round - 1)),
+            requester: String::from(CONTROLLER_ADDRESS),
+            group_index: assignment_group_index,
+            request_block_height: self.block_height,
+            assignment_block_height: self.block_height,
+            beacon_round: Some(round),
+            fee: 0,
+            eligible_members: self.eligible_members(assignment_group_index),
+        };
 
-            let public_key = bincode::deserialize(public_key_as_bytes).unwrap();
+        self.pending_signature_tasks
+            .insert(signature_task.index, signature_task);
+    }
 
-            // Note: partial_signature contains participant index
-            let res = SigScheme::verify(&public_key, message.as_bytes(), partial_signature);
+    fn sweep_stuck_groups(&mut self) {
+        let deadline = match self.dkg_commit_deadline {
+            Some(deadline) => deadline,
+            None => return,
+        };
 
-            match res {
-                Ok(()) => {}
-                Err(_err) => {
-                    committer.staking -= COMMITTER_PENALTY_PER_SIGNATURE;
+        let dkg_tasks = &self.dkg_tasks;
 
-                    self.freeze_node(committer_address, 0);
+        let block_height = self.block_height;
 
-                    if !self.rewards.contains_key(&id_address) {
-                        self.rewards.insert(id_address.clone(), 0);
-                    }
+        let stuck_group_indices = self
+            .groups
+            .values()
+            .filter(|group| group.pending_epoch.is_some())
+            .filter(|group| {
+                let assigned_at = dkg_tasks
+                    .iter()
+                    .rev()
+                    .find(|task| task.group_index == group.index)
+                    .map(|task| task.assignment_block_height);
+
+                matches!(assigned_at, Some(assigned_at) if assigned_at + deadline <= block_height)
+            })
+            .map(|group| group.index)
+            .collect::<Vec<_>>();
+
+        for group_index in stuck_group_indices {
+            let group = self.groups.get(&group_index).unwrap();
+
+            // nobody committing says nothing about who is at fault
+            let absent_members = if group.commit_cache.is_empty() {
+                vec![]
+            } else {
+                group
+                    .members
+                    .keys()
+                    .filter(|id_address| !group.commit_cache.contains_key(*id_address))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            };
 
-                    let challenger_reward = self.rewards.get_mut(&id_address).unwrap();
+            // like disqualified nodes, absent members leave the group
+            for id_address in absent_members {
+                self.remove_from_group(&id_address, group_index, false);
 
-                    *challenger_reward += CHALLENGE_REWARD_PER_SIGNATURE;
+                self.slash_node(&id_address, DKG_ABSENCE_PENALTY);
+            }
 
-                    self.verifiable_signature_rewards.remove(&signature_index);
+            let group = self.groups.get_mut(&group_index).unwrap();
 
-                    return true;
-                }
+            if group.size >= group.minimum_threshold {
+                self.emit_group_event(group_index);
+            } else if group.size == 0 {
+                group.fall_back();
+            } else {
+                self.rebalance_or_escalate(group_index);
             }
         }
+    }
+
+    fn slash_node(&mut self, id_address: &str, amount: usize) {
+        let node = self.nodes.get_mut(id_address).unwrap();
+
+        // the slashed stake stays in the controller's balance
+        node.staking = node.staking.saturating_sub(amount);
 
-        self.verifiable_signature_rewards.remove(&signature_index);
+        self.reward_statements
+            .entry(id_address.to_string())
+            .or_default()
+            .slashed += amount;
 
-        false
+        self.freeze_node(id_address, 0);
     }
-}
 
-impl Views for Controller {
-    fn get_last_output(&self) -> u64 {
-        self.last_output
+    fn record_activity(&mut self, id_address: &str) {
+        if let Some(node) = self.nodes.get_mut(id_address) {
+            node.last_active_block = self.block_height;
+        }
     }
 
-    fn get_node(&self, id_address: String) -> &Node {
-        self.nodes.get(&id_address).unwrap()
+    fn sweep_unresponsive_nodes(&mut self) {
+        let timeout = match self.liveness_timeout {
+            Some(timeout) => timeout,
+            None => return,
+        };
+
+        let unresponsive_nodes = self
+            .nodes
+            .values()
+            .filter(|node| node.state && node.last_active_block + timeout <= self.block_height)
+            .map(|node| node.id_address.clone())
+            .collect::<Vec<_>>();
+
+        for id_address in unresponsive_nodes {
+            self.freeze_node(&id_address, 0);
+        }
     }
+}
 
-    fn get_group(&self, index: usize) -> &Group {
-        self.groups.get(&index).unwrap()
+impl MockHelper for Controller {
+    fn emit_dkg_task(&self, from_index: usize) -> Vec<&DKGTask> {
+        self.dkg_tasks.iter().skip(from_index).collect::<Vec<_>>()
     }
 
-    fn valid_group_indices(&self) -> Vec<usize> {
-        self.groups
-            .values()
-            .filter(|g| g.state)
-            .map(|g| g.index)
+    fn emit_dkg_task_for_node(&self, id_address: &str, from_index: usize) -> Vec<&DKGTask> {
+        self.dkg_tasks
+            .iter()
+            .skip(from_index)
+            .filter(|task| task.members.contains_key(id_address))
             .collect::<Vec<_>>()
     }
 
-    fn pending_signature_tasks(&self) -> Vec<&SignatureTask> {
-        self.pending_signature_tasks.values().collect::<Vec<_>>()
+    fn backfill_dkg_tasks(&self, id_address: &str) -> Vec<(&DKGTask, DKGCatchUp)> {
+        // a later task of the same group supersedes the earlier ones
+        let latest_tasks = self
+            .emit_dkg_task_for_node(id_address, 0)
+            .into_iter()
+            .map(|task| (task.group_index, task))
+            .collect::<BTreeMap<_, _>>();
+
+        latest_tasks
+            .into_iter()
+            .filter_map(|(group_index, task)| {
+                let group = self.groups.get(&group_index)?;
+
+                if group.epoch != task.epoch
+                    || !group.members.contains_key(id_address)
+                    || group.commit_cache.contains_key(id_address)
+                {
+                    return None;
+                }
+
+                let coordinator = self
+                    .coordinators
+                    .get(&group_index)
+                    .filter(|coordinator| coordinator.epoch == task.epoch);
+
+                match coordinator {
+                    Some(coordinator) if coordinator.in_phase() == Some(1) => {
+                        Some((task, DKGCatchUp::Join))
+                    }
+                    Some(_) => Some((task, DKGCatchUp::AwaitCheckDkgState)),
+                    None if group.state == GroupState::AwaitingCommits => {
+                        Some((task, DKGCatchUp::AwaitCheckDkgState))
+                    }
+                    None => None,
+                }
+            })
+            .collect::<Vec<_>>()
     }
 
-    fn verifiable_signature_rewards(&self) -> Vec<&SignatureReward> {
-        self.verifiable_signature_rewards
-            .values()
+    fn emit_signature_task(&self, from_index: TaskIndex) -> Vec<&SignatureTask> {
+        self.pending_signature_tasks
+            .range(from_index..)
+            .map(|(_, task)| task)
             .collect::<Vec<_>>()
     }
-}
 
-#[cfg(test)]
-pub mod tests {
+    fn emit_challenge_event(&self, from_index: usize) -> Vec<&ChallengeEvent> {
+        self.challenge_events
+            .iter()
+            .skip(from_index)
+            .collect::<Vec<_>>()
+    }
+
+    fn get_coordinator_mut(&mut self, group_index: GroupIndex) -> Option<&mut Coordinator> {
+        self.coordinators.get_mut(&group_index)
+    }
+
+    fn mine(&mut self, block_number: usize) {
+        self.block_height += block_number;
+
+        for coordinator in self.coordinators.values_mut() {
+            coordinator.block_height = self.block_height;
+        }
+
+        self.sweep_unresponsive_nodes();
+
+        self.sweep_stuck_groups();
+
+        self.emit_beacon_task();
+    }
+}
+
+impl Transactions for Controller {
+    fn node_register(
+        &mut self,
+        id_address: String,
+        id_public_key: Vec<u8>,
+        endpoint: String,
+        reward_address: String,
+        capabilities: Capabilities,
+    ) -> bool {
+        if self.nodes.contains_key(&id_address)
+            || self.blacklist.contains(&id_address)
+            || capabilities.check(self.scheme).is_err()
+        {
+            return false;
+        }
+
+        // the node approves the controller to pull its stake beforehand
+        if self
+            .token
+            .transfer_from(
+                CONTROLLER_ADDRESS,
+                &id_address,
+                CONTROLLER_ADDRESS,
+                NODE_STAKING_AMOUNT,
+            )
+            .is_err()
+        {
+            return false;
+        }
+
+        let node = Node {
+            id_address: id_address.clone(),
+            id_public_key,
+            endpoint,
+            reward_address,
+            state: true,
+            pending_until_block: 0,
+            staking: NODE_STAKING_AMOUNT,
+            capabilities,
+            last_active_block: self.block_height,
+        };
+
+        self.nodes.insert(id_address.clone(), node);
+
+        self.rewards.insert(id_address.clone(), 0);
+
+        self.node_join(&id_address);
+
+        true
+    }
+
+    fn update_node_key(
+        &mut self,
+        id_address: String,
+        id_public_key: Vec<u8>,
+        signature: Vec<u8>,
+        proof_of_possession: Vec<u8>,
+    ) -> bool {
+        let node = match self.nodes.get(&id_address) {
+            Some(node) => node,
+            None => return false,
+        };
+
+        let message = node_key_update_message(&id_address, &node.id_public_key, &id_public_key);
+
+        let keys = (
+            bincode::deserialize::<G1>(&node.id_public_key),
+            bincode::deserialize::<G1>(&id_public_key),
+        );
+
+        let (old_key, new_key) = match keys {
+            (Ok(old_key), Ok(new_key)) => (old_key, new_key),
+            _ => return false,
+        };
+
+        if SigScheme::verify(&old_key, message.as_bytes(), &signature).is_err()
+            || SigScheme::verify(&new_key, message.as_bytes(), &proof_of_possession).is_err()
+        {
+            return false;
+        }
+
+        self.nodes.get_mut(&id_address).unwrap().id_public_key = id_public_key;
+
+        // the shares of the current key were encrypted to the old key
+        let group_indices = self
+            .groups
+            .values()
+            .filter(|group| group.members.contains_key(&id_address))
+            .map(|group| group.index)
+            .collect::<BTreeSet<_>>();
+
+        for group_index in group_indices {
+            self.emit_group_event(group_index);
+        }
+
+        true
+    }
+
+    fn node_quit(&mut self, _id_address: String) {
+        todo!()
+    }
+
+    fn node_activate(&mut self, id_address: String) -> bool {
+        let node = match self.nodes.get(&id_address) {
+            Some(node) if !node.state => node,
+            _ => return false,
+        };
+
+        if self.blacklist.contains(&id_address) || self.block_height < node.pending_until_block {
+            return false;
+        }
+
+        // what got slashed while the node was frozen
+        let top_up = NODE_STAKING_AMOUNT.saturating_sub(node.staking);
+
+        if top_up > 0
+            && self
+                .token
+                .transfer_from(CONTROLLER_ADDRESS, &id_address, CONTROLLER_ADDRESS, top_up)
+                .is_err()
+        {
+            return false;
+        }
+
+        let block_height = self.block_height;
+
+        let node = self.nodes.get_mut(&id_address).unwrap();
+
+        node.state = true;
+
+        node.pending_until_block = 0;
+
+        node.staking += top_up;
+
+        // or it would be frozen again for the silence it was frozen for
+        node.last_active_block = block_height;
+
+        self.node_join(&id_address);
+
+        true
+    }
+
+    fn redeem(&mut self, _id_address: String) {
+        todo!()
+    }
+
+    fn claim(&mut self, id_address: String) -> bool {
+        let amount = self.rewards.get(&id_address).cloned().unwrap_or(0);
+
+        if amount == 0 {
+            return false;
+        }
+
+        if self
+            .token
+            .transfer(CONTROLLER_ADDRESS, &id_address, amount)
+            .is_err()
+        {
+            return false;
+        }
+
+        self.rewards.insert(id_address.clone(), 0);
+
+        self.reward_statements
+            .entry(id_address)
+            .or_default()
+            .claimed += amount;
+
+        true
+    }
+
+    fn commit_dkg(
+        &mut self,
+        id_address: String,
+        group_index: GroupIndex,
+        group_epoch: Epoch,
+        public_key: Vec<u8>,
+        partial_public_key: Vec<u8>,
+        disqualified_nodes: Vec<String>,
+    ) -> bool {
+        let group = match self.groups.get_mut(&group_index) {
+            Some(group) => group,
+            None => return false,
+        };
+
+        if !group.members.contains_key(&id_address) || group.epoch != group_epoch {
+            return false;
+        }
+
+        let commit_result = CommitResult {
+            group_epoch,
+            public_key,
+            disqualified_nodes,
+        };
+
+        let commit_cache = CommitCache {
+            commit_result,
+            partial_public_key: partial_public_key.clone(),
+        };
+
+        if group.commit_cache.contains_key(&id_address) {
+            return false;
+        }
+
+        fn get_identical_over_threshold_commitment(
+            controller: &Controller,
+            group_index: GroupIndex,
+        ) -> Option<CommitCache> {
+            let group = controller.groups.get(&group_index).unwrap();
+
+            let mut map: HashMap<u64, usize> = HashMap::new();
+
+            for commit_cache in group.commit_cache.values() {
+                let count = map
+                    .entry(controller.mixing.mix(&commit_cache.commit_result))
+                    .or_insert(0);
+
+                *count += 1;
+
+                if *count >= group.threshold {
+                    return Some(commit_cache.clone());
+                }
+            }
+
+            None
+        }
+
+        // the commit cache only holds commitments of the current epoch
+        let consensus_reached =
+            get_identical_over_threshold_commitment(self, group_index).is_some();
+
+        self.record_activity(&id_address);
+
+        self.participation
+            .entry(id_address.clone())
+            .or_default()
+            .dkg_commits += 1;
+
+        // shares are indexed like the members of the task the DKG ran for, which removals
+        // since then don't change
+        let share_indices = self
+            .dkg_tasks
+            .iter()
+            .rev()
+            .find(|task| task.group_index == group_index && task.epoch == group_epoch)
+            .map(|task| task.members.clone())
+            .unwrap_or_default();
+
+        let group = self.groups.get_mut(&group_index).unwrap();
+
+        group.commit_cache.insert(id_address.clone(), commit_cache);
+
+        if consensus_reached {
+            // it's no good for a qualified node to miscommits here. So far we don't verify this commitment.
+            if let Some(member) = group.members.get_mut(&id_address) {
+                member.partial_public_key = partial_public_key;
+
+                member.share_index = share_indices
+                    .get(&id_address)
+                    .map(|index| index.dkg_index());
+            }
+
+            return true;
+        }
+
+        let commit_cache = match get_identical_over_threshold_commitment(self, group_index) {
+            None => return true,
+            Some(commit_cache) => commit_cache,
+        };
+
+        // the threshold the key was generated with
+        let dkg_threshold = self.groups[&group_index].threshold;
+
+        // the committed key supersedes the previous one, whose threshold the disqualified
+        // members leaving must not be checked against
+        self.groups.get_mut(&group_index).unwrap().key_threshold = 0;
+
+        for disqualified_id_address in commit_cache.commit_result.disqualified_nodes.iter() {
+            if self.groups[&group_index]
+                .members
+                .contains_key(disqualified_id_address)
+            {
+                self.remove_from_group(disqualified_id_address, group_index, false);
+            }
+        }
+
+        let group = self.groups.get_mut(&group_index).unwrap();
+
+        group.committers.clear();
+
+        // the threshold of the generated key can't shrink with the group, so the group
+        // is only usable if enough members are retained to reach it
+        if group.size < group.minimum_threshold {
+            group.fall_back();
+
+            self.rebalance_or_escalate(group_index);
+
+            return true;
+        }
+
+        if group.size < dkg_threshold {
+            group.set_state(GroupState::AwaitingCommits);
+
+            self.emit_group_event(group_index);
+
+            return true;
+        }
+
+        group.threshold = dkg_threshold;
+
+        group.key_threshold = dkg_threshold;
+
+        group.pending_epoch = None;
+
+        group.set_state(GroupState::Active);
+
+        group.public_key = commit_cache.commit_result.public_key.clone();
+
+        // members who didn't commit keep nothing of the previous key, until they commit late
+        for (id_address, member) in group.members.iter_mut() {
+            match group.commit_cache.get(id_address) {
+                Some(cache) => {
+                    member.partial_public_key = cache.partial_public_key.clone();
+
+                    member.share_index =
+                        share_indices.get(id_address).map(|index| index.dkg_index());
+                }
+                None => {
+                    member.partial_public_key = vec![];
+
+                    member.share_index = None;
+                }
+            }
+        }
+
+        let committers = self.choose_committers(group_index);
+
+        self.groups.get_mut(&group_index).unwrap().committers = committers;
+
+        // tasks waiting for the group are signed with the new key by its new members
+        let eligible_members = self.eligible_members(group_index);
+
+        self.pending_signature_tasks
+            .values_mut()
+            .filter(|task| task.group_index == group_index)
+            .for_each(|task| task.eligible_members = eligible_members.clone());
+
+        true
+    }
+
+    fn request(&mut self, requester: String, seed: Vec<u8>, num_words: usize) -> bool {
+        if self.valid_group_indices().is_empty() {
+            return false;
+        }
+
+        let fee = request_fee(num_words);
+
+        if self
+            .token
+            .transfer_from(CONTROLLER_ADDRESS, &requester, CONTROLLER_ADDRESS, fee)
+            .is_err()
+        {
+            return false;
+        }
+
+        let assignment_group_index = self.next_valid_group_index().unwrap();
+
+        self.signature_count += 1;
+
+        let signature_task = SignatureTask {
+            index: TaskIndex(self.signature_count),
+            seed,
+            requester,
+            group_index: assignment_group_index,
+            request_block_height: self.block_height,
+            assignment_block_height: self.block_height,
+            beacon_round: None,
+            fee,
+            eligible_members: self.eligible_members(assignment_group_index),
+        };
+
+        self.pending_signature_tasks
+            .insert(signature_task.index, signature_task);
+
+        true
+    }
+
+    fn fulfill(
+        &mut self,
+        id_address: String,
+        signature_index: TaskIndex,
+        signature: Vec<u8>,
+        partial_signatures: HashMap<String, Vec<u8>>,
+    ) -> bool {
+        if !self.pending_signature_tasks.contains_key(&signature_index) {
+            return false;
+        }
+
+        let signature_task = self
+            .pending_signature_tasks
+            .get(&signature_index)
+            .unwrap()
+            .clone();
+
+        let group = self
+            .groups
+            .get(&signature_task.group_index)
+            .unwrap()
+            .clone();
+
+        if !group.committers.contains(&id_address) {
+            return false;
+        }
+
+        if check_fulfillment_sizes(
+            signature_task.eligible_members.len(),
+            &signature,
+            &partial_signatures,
+        )
+        .is_err()
+        {
+            return false;
+        }
+
+        let message = signature_task.message();
+
+        if check_partial_signatures(
+            &signature_task.eligible_members,
+            group.key_threshold,
+            &signature,
+            &partial_signatures,
+            &mut self.lagrange_cache,
+        )
+        .is_err()
+        {
+            return false;
+        }
+
+        // the key is whatever the members agreed on, it may not be a point at all
+        let group_public_key: G1 = match bincode::deserialize(&group.public_key) {
+            Ok(group_public_key) => group_public_key,
+            Err(_) => return false,
+        };
+
+        match SigScheme::verify(&group_public_key, &message, &signature) {
+            Ok(()) => {}
+            Err(_err) => return false,
+        }
+
+        let committer = self.nodes.get(&id_address).unwrap();
+
+        let committer_address = committer.id_address.clone();
+
+        let committer_reward_address = committer.reward_address.clone();
+
+        self.accrue_reward(
+            &committer_address,
+            &committer_reward_address,
+            RewardSource::Committer,
+            group.epoch,
+            COMMITTER_REWARD_PER_SIGNATURE,
+        );
+
+        // members are paid at the reward address they had when the task was assigned
+        partial_signatures.keys().for_each(|member_id_address| {
+            let reward_address = &signature_task.eligible_members[member_id_address].reward_address;
+
+            self.accrue_reward(
+                member_id_address,
+                reward_address,
+                RewardSource::Signature,
+                group.epoch,
+                REWARD_PER_SIGNATURE,
+            );
+        });
+
+        for member_id_address in signature_task.eligible_members.keys() {
+            let stats = self
+                .participation
+                .entry(member_id_address.clone())
+                .or_default();
+
+            if partial_signatures.contains_key(member_id_address) {
+                stats.partial_signatures += 1;
+            } else {
+                stats.missed_tasks += 1;
+            }
+        }
+
+        let committer_fee = signature_task.fee * COMMITTER_FEE_SHARE / 100;
+
+        self.accrue_reward(
+            &committer_address,
+            &committer_reward_address,
+            RewardSource::Committer,
+            group.epoch,
+            committer_fee,
+        );
+
+        // an equal split unless members are weighted by how often they sign
+        let mut member_weights = partial_signatures
+            .keys()
+            .map(|member_id_address| {
+                let weight = if self.participation_weighting {
+                    self.participation[member_id_address].signing_rate()
+                } else {
+                    1
+                };
+
+                (member_id_address, weight)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut total_weight = member_weights.values().sum::<usize>();
+
+        // signers who all sign too rarely to weigh anything share equally
+        if total_weight == 0 {
+            member_weights.values_mut().for_each(|weight| *weight = 1);
+
+            total_weight = member_weights.len();
+        }
+
+        let members_fee = signature_task.fee * MEMBERS_FEE_SHARE / 100;
+
+        let mut paid_member_fees = 0;
+
+        for (member_id_address, weight) in member_weights {
+            let member_fee = members_fee * weight / total_weight;
+
+            let reward_address = &signature_task.eligible_members[member_id_address].reward_address;
+
+            self.accrue_reward(
+                member_id_address,
+                reward_address,
+                RewardSource::Signature,
+                group.epoch,
+                member_fee,
+            );
+
+            paid_member_fees += member_fee;
+        }
+
+        // including what is left over from splitting the members' share
+        self.treasury += signature_task.fee - committer_fee - paid_member_fees;
+
+        self.last_output = self.mixing.mix(&signature);
+
+        if let Some(round) = signature_task.beacon_round {
+            self.beacons.insert(round, signature.clone());
+        }
+
+        let signature_reward = SignatureReward {
+            signature_task,
+            committer: committer_address,
+            group_epoch: group.epoch,
+            partial_signatures,
+        };
+
+        self.record_activity(&signature_reward.committer);
+
+        for member_id_address in signature_reward.partial_signatures.keys() {
+            self.record_activity(member_id_address);
+        }
+
+        self.verifiable_signature_rewards
+            .insert(signature_index, signature_reward);
+
+        self.pending_signature_tasks.remove(&signature_index);
+
+        self.emit_beacon_task();
+
+        true
+    }
+
+    fn challenge_reward(&mut self, id_address: String, signature_index: TaskIndex) -> bool {
+        let signature_reward = match self.verifiable_signature_rewards.remove(&signature_index) {
+            Some(signature_reward) => signature_reward,
+            None => return false,
+        };
+
+        let signature_task = &signature_reward.signature_task;
+
+        let invalid_partial_signer = find_invalid_partial_signer(
+            &signature_task.eligible_members,
+            &signature_task.message(),
+            &signature_reward.partial_signatures,
+        );
+
+        if invalid_partial_signer.is_some() {
+            self.slash_node(&signature_reward.committer, COMMITTER_PENALTY_PER_SIGNATURE);
+
+            self.accrue_reward(
+                &id_address,
+                &id_address,
+                RewardSource::Challenge,
+                signature_reward.group_epoch,
+                CHALLENGE_REWARD_PER_SIGNATURE,
+            );
+        }
+
+        let challenged = invalid_partial_signer.is_some();
+
+        self.challenge_events.push(ChallengeEvent {
+            signature_index,
+            challenger: id_address,
+            committer: signature_reward.committer,
+            signature_task: signature_reward.signature_task,
+            partial_signatures: signature_reward.partial_signatures,
+            invalid_partial_signer,
+        });
+
+        challenged
+    }
+
+    fn disband_group(&mut self, id_address: String, group_index: GroupIndex) -> bool {
+        if id_address != self.owner || !self.groups.contains_key(&group_index) {
+            return false;
+        }
+
+        let group = self.groups.get_mut(&group_index).unwrap();
+
+        // members rejoin in index order so that replaying the same transactions
+        // regroups them the same way
+        let mut freed_members = group.members.values().collect::<Vec<_>>();
+
+        freed_members.sort_by_key(|member| member.index);
+
+        let freed_members = freed_members
+            .into_iter()
+            .map(|member| member.id_address.clone())
+            .collect::<Vec<_>>();
+
+        group.set_state(GroupState::Disbanded);
+        group.size = 0;
+        group.threshold = group.minimum_threshold;
+        group.key_threshold = 0;
+        group.public_key = vec![];
+        group.members.clear();
+        group.committers.clear();
+        group.commit_cache.clear();
+        group.pending_epoch = None;
+
+        // in-flight signature tasks move to the next valid group, or stay pending
+        // until the disbanded group is formed again
+        let in_flight_indices = self
+            .pending_signature_tasks
+            .values()
+            .filter(|task| task.group_index == group_index)
+            .map(|task| task.index)
+            .collect::<Vec<_>>();
+
+        for signature_index in in_flight_indices {
+            if let Some(assignment_group_index) = self.next_valid_group_index() {
+                let eligible_members = self.eligible_members(assignment_group_index);
+
+                let signature_task = self
+                    .pending_signature_tasks
+                    .get_mut(&signature_index)
+                    .unwrap();
+
+                signature_task.group_index = assignment_group_index;
+
+                signature_task.assignment_block_height = self.block_height;
+
+                signature_task.eligible_members = eligible_members;
+            }
+        }
+
+        // frees members back to the join pool
+        for member_id_address in freed_members {
+            if self.nodes.get(&member_id_address).unwrap().state {
+                self.node_join(&member_id_address);
+            }
+        }
+
+        true
+    }
+
+    fn check_dkg_state(&mut self, id_address: String, group_index: GroupIndex) -> bool {
+        if !self.nodes.contains_key(&id_address) {
+            return false;
+        }
+
+        match self.coordinators.get(&group_index) {
+            Some(coordinator) if coordinator.in_phase().is_none() => {}
+            _ => return false,
+        }
+
+        let coordinator = self.coordinators.remove(&group_index).unwrap();
+
+        self.archive_coordinator(coordinator);
+
+        true
+    }
+
+    fn set_group_config(&mut self, id_address: String, config: GroupConfig) -> bool {
+        if id_address != self.owner || config.validate().is_err() {
+            return false;
+        }
+
+        self.group_config = config;
+
+        true
+    }
+
+    fn create_group(&mut self, id_address: String, config: GroupConfig) -> bool {
+        if id_address != self.owner || config.validate().is_err() {
+            return false;
+        }
+
+        self.add_group(config);
+
+        true
+    }
+
+    fn blacklist_node(&mut self, id_address: String, node: String) -> bool {
+        if id_address != self.owner || !self.blacklist.insert(node.clone()) {
+            return false;
+        }
+
+        if matches!(self.nodes.get(&node), Some(registered) if registered.state) {
+            self.freeze_node(&node, 0);
+        }
+
+        true
+    }
+
+    fn unblacklist_node(&mut self, id_address: String, node: String) -> bool {
+        id_address == self.owner && self.blacklist.remove(&node)
+    }
+
+    fn node_heartbeat(&mut self, id_address: String) -> bool {
+        if !matches!(self.nodes.get(&id_address), Some(node) if node.state) {
+            return false;
+        }
+
+        self.record_activity(&id_address);
+
+        true
+    }
+}
+
+impl Views for Controller {
+    fn get_last_output(&self) -> u64 {
+        self.last_output
+    }
+
+    fn get_node(&self, id_address: String) -> &Node {
+        self.nodes.get(&id_address).unwrap()
+    }
+
+    fn get_group(&self, index: GroupIndex) -> &Group {
+        self.groups.get(&index).unwrap()
+    }
+
+    fn find_group(&self, index: GroupIndex) -> Option<&Group> {
+        self.groups.get(&index)
+    }
+
+    fn get_group_state(&self, index: GroupIndex) -> Option<GroupState> {
+        self.groups.get(&index).map(|group| group.state)
+    }
+
+    fn get_blacklisted_nodes(&self) -> Vec<&String> {
+        self.blacklist.iter().collect()
+    }
+
+    fn get_participation_stats(&self, id_address: &str) -> Option<&ParticipationStats> {
+        self.participation.get(id_address)
+    }
+
+    fn valid_group_indices(&self) -> Vec<GroupIndex> {
+        self.groups
+            .values()
+            .filter(|g| g.state == GroupState::Active)
+            .map(|g| g.index)
+            .collect::<Vec<_>>()
+    }
+
+    fn pending_signature_tasks(&self) -> Vec<&SignatureTask> {
+        self.pending_signature_tasks.values().collect::<Vec<_>>()
+    }
+
+    fn verifiable_signature_rewards(&self) -> Vec<&SignatureReward> {
+        self.verifiable_signature_rewards
+            .values()
+            .collect::<Vec<_>>()
+    }
+
+    fn get_coordinator(&self, group_index: GroupIndex) -> Option<&Coordinator> {
+        self.coordinators.get(&group_index)
+    }
+
+    fn get_archived_coordinator(
+        &self,
+        group_index: GroupIndex,
+        epoch: Epoch,
+    ) -> Option<&Coordinator> {
+        self.archived_coordinators.iter().find(|coordinator| {
+            coordinator.group_index == group_index && coordinator.epoch == epoch
+        })
+    }
+
+    fn get_dkg_transcript(&self, group_index: GroupIndex, epoch: Epoch) -> Option<DKGTranscript> {
+        self.coordinators
+            .get(&group_index)
+            .filter(|coordinator| coordinator.epoch == epoch)
+            .or_else(|| self.get_archived_coordinator(group_index, epoch))
+            .map(|coordinator| coordinator.get_transcript())
+    }
+
+    fn simulate_commit_dkg(
+        &self,
+        id_address: String,
+        group_index: GroupIndex,
+        group_epoch: Epoch,
+        public_key: Vec<u8>,
+        partial_public_key: Vec<u8>,
+        disqualified_nodes: Vec<String>,
+    ) -> bool {
+        self.clone().commit_dkg(
+            id_address,
+            group_index,
+            group_epoch,
+            public_key,
+            partial_public_key,
+            disqualified_nodes,
+        )
+    }
+
+    fn simulate_fulfill(
+        &self,
+        id_address: String,
+        signature_index: TaskIndex,
+        signature: Vec<u8>,
+        partial_signatures: HashMap<String, Vec<u8>>,
+    ) -> bool {
+        self.clone()
+            .fulfill(id_address, signature_index, signature, partial_signatures)
+    }
+
+    fn state_hash(&self) -> u64 {
+        let chain = (
+            self.block_height,
+            self.epoch,
+            self.signature_count,
+            self.last_output,
+            self.last_group_index,
+            self.group_config,
+            &self.blacklist,
+        );
+
+        let groups = self
+            .groups
+            .iter()
+            .map(|(index, group)| (index, group.state_hash(self.mixing)))
+            .collect::<Vec<_>>();
+
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|(id_address, node)| {
+                (
+                    id_address,
+                    &node.id_public_key,
+                    &node.endpoint,
+                    &node.reward_address,
+                    node.state,
+                    node.pending_until_block,
+                    node.staking,
+                    &node.capabilities,
+                    node.last_active_block,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // hash maps are put in key order first
+        let ledger = (
+            &self.token,
+            self.treasury,
+            self.rewards.iter().collect::<BTreeMap<_, _>>(),
+            &self.participation,
+            self.reward_statements.iter().collect::<BTreeMap<_, _>>(),
+        );
+
+        let verifiable_signature_rewards = self
+            .verifiable_signature_rewards
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .map(|(index, reward)| {
+                (
+                    index,
+                    &reward.signature_task,
+                    &reward.committer,
+                    reward.partial_signatures.iter().collect::<BTreeMap<_, _>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let challenge_events = self
+            .challenge_events
+            .iter()
+            .map(|event| {
+                (
+                    event.signature_index,
+                    &event.challenger,
+                    &event.committer,
+                    &event.invalid_partial_signer,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let signatures = (
+            self.pending_signature_tasks.values().collect::<Vec<_>>(),
+            verifiable_signature_rewards,
+            challenge_events,
+        );
+
+        let dkg_tasks = self
+            .dkg_tasks
+            .iter()
+            .map(|task| {
+                (
+                    task.index,
+                    task.group_index,
+                    task.epoch,
+                    task.size,
+                    task.threshold,
+                    task.assignment_block_height,
+                    &task.members,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let transcripts = self
+            .coordinators
+            .values()
+            .chain(self.archived_coordinators.iter())
+            .map(|coordinator| coordinator.get_transcript())
+            .collect::<Vec<_>>();
+
+        self.mixing.mix(&(
+            chain,
+            groups,
+            nodes,
+            ledger,
+            signatures,
+            dkg_tasks,
+            transcripts,
+            &self.beacons,
+        ))
+    }
+
+    fn group_state_hash(&self, group_index: GroupIndex) -> Option<u64> {
+        self.groups
+            .get(&group_index)
+            .map(|group| group.state_hash(self.mixing))
+    }
+
+    fn get_group_member_keys(
+        &self,
+        group_index: GroupIndex,
+        epoch: Epoch,
+    ) -> Option<BTreeMap<String, Vec<u8>>> {
+        let group = self
+            .groups
+            .get(&group_index)
+            .filter(|group| group.state == GroupState::Active && group.epoch == epoch)?;
+
+        // members who never committed have no key to share
+        let member_keys = group
+            .members
+            .iter()
+            .filter(|(_, member)| !member.partial_public_key.is_empty())
+            .map(|(id_address, member)| (id_address.clone(), member.partial_public_key.clone()))
+            .collect();
+
+        Some(member_keys)
+    }
+
+    fn get_beacon(&self, round: usize) -> Option<&Vec<u8>> {
+        self.beacons.get(&round)
+    }
+
+    fn get_treasury(&self) -> usize {
+        self.treasury
+    }
+
+    fn get_reward_statement(&self, id_address: &str) -> Option<&RewardStatement> {
+        self.reward_statements.get(id_address)
+    }
+
+    fn latest_beacon_round(&self) -> usize {
+        self.beacons.keys().last().cloned().unwrap_or(0)
+    }
+
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        let mut memberships: HashMap<&String, GroupIndex> = HashMap::new();
+
+        for group in self.groups.values() {
+            if group.size != group.members.len() {
+                return Err(InvariantViolation::SizeMismatch(
+                    group.index,
+                    group.size,
+                    group.members.len(),
+                ));
+            }
+
+            if group.state == GroupState::Active && group.size < group.key_threshold {
+                return Err(InvariantViolation::ValidBelowThreshold(
+                    group.index,
+                    group.size,
+                    group.key_threshold,
+                ));
+            }
+
+            let member_indices = group
+                .members
+                .values()
+                .map(|member| member.index.0)
+                .collect::<BTreeSet<_>>();
+
+            if !member_indices.iter().copied().eq(1..=group.size) {
+                return Err(InvariantViolation::NonContiguousMemberIndices(group.index));
+            }
+
+            for id_address in group.members.keys() {
+                match self.nodes.get(id_address) {
+                    None => {
+                        return Err(InvariantViolation::UnknownMember(
+                            group.index,
+                            id_address.clone(),
+                        ))
+                    }
+                    Some(node) if !node.state => {
+                        return Err(InvariantViolation::FrozenMember(
+                            group.index,
+                            id_address.clone(),
+                        ))
+                    }
+                    _ => {}
+                }
+
+                if let Some(other) = memberships.insert(id_address, group.index) {
+                    return Err(InvariantViolation::MemberOfSeveralGroups(
+                        id_address.clone(),
+                        other,
+                        group.index,
+                    ));
+                }
+            }
+
+            if let Some(committer) = group
+                .committers
+                .iter()
+                .find(|committer| !group.members.contains_key(*committer))
+            {
+                return Err(InvariantViolation::CommitterNotAMember(
+                    group.index,
+                    committer.clone(),
+                ));
+            }
+        }
+
+        if let Some(task) = self
+            .pending_signature_tasks
+            .values()
+            .find(|task| !self.groups.contains_key(&task.group_index))
+        {
+            return Err(InvariantViolation::UnknownAssignedGroup(
+                task.index,
+                task.group_index,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn get_group_config(&self) -> &GroupConfig {
+        &self.group_config
+    }
+}
+
+/// The message signed for a beacon round, chained to the previous round like drand does:
+/// the previous signature (none for the first round) followed by the big endian round
+/// number.
+pub fn beacon_message(round: usize, previous_signature: Option<&Vec<u8>>) -> Vec<u8> {
+    let mut message = previous_signature.cloned().unwrap_or_default();
+
+    message.extend_from_slice(&(round as u64).to_be_bytes());
+
+    message
+}
+
+/// The message signed for a request: the bincode encoding of the seed, the requester and
+/// the block height the request was made at. Every field is length prefixed or fixed
+/// size, so no two requests share a message.
+pub fn request_message(seed: &[u8], requester: &str, block_height: usize) -> Vec<u8> {
+    bincode::serialize(&(seed, requester, block_height as u64)).expect("could not serialize")
+}
+
+/// The message signed by both the current and the new id key of a node to rotate its
+/// key. It includes the current key so that the update can't be replayed later on.
+pub fn node_key_update_message(
+    id_address: &str,
+    id_public_key: &[u8],
+    new_id_public_key: &[u8],
+) -> String {
+    format!(
+        "{}:{}:{}",
+        id_address,
+        hex::encode(id_public_key),
+        hex::encode(new_id_public_key)
+    )
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use threshold_bls::poly::Poly;
+
+    const OWNER: &str = "0xadmin";
+
+    /// Mints the stake of the node and approves the controller to pull it.
+    pub fn fund_stake(controller: &mut Controller, id_address: &str) {
+        controller.token.mint(id_address, NODE_STAKING_AMOUNT);
+
+        controller
+            .token
+            .approve(id_address, CONTROLLER_ADDRESS, NODE_STAKING_AMOUNT);
+    }
+
+    pub fn register_node(controller: &mut Controller, id_address: &str) -> bool {
+        fund_stake(controller, id_address);
+
+        controller.node_register(
+            id_address.to_string(),
+            vec![],
+            String::from(""),
+            id_address.to_string(),
+            Capabilities::default(),
+        )
+    }
+
+    pub const REQUESTER: &str = "0xrequester";
+
+    /// Funds the requester with the fee of a single word request and requests it.
+    pub fn request_randomness(controller: &mut Controller, message: &str) -> bool {
+        controller.token.mint(REQUESTER, request_fee(1));
+
+        controller
+            .token
+            .approve(REQUESTER, CONTROLLER_ADDRESS, request_fee(1));
+
+        controller.request(String::from(REQUESTER), message.as_bytes().to_vec(), 1)
+    }
+
+    pub fn register_nodes(controller: &mut Controller, n: usize) {
+        (0..n).for_each(|i| {
+            register_node(controller, &format!("0x{}", i));
+        });
+    }
+
+    fn commit_latest_dkg_task(controller: &mut Controller, public_key: Vec<u8>) {
+        let dkg_task = (*controller.emit_dkg_task(0).last().unwrap()).clone();
+
+        dkg_task.members.keys().for_each(|id_address| {
+            controller.commit_dkg(
+                id_address.clone(),
+                dkg_task.group_index,
+                dkg_task.epoch,
+                public_key.clone(),
+                vec![],
+                vec![],
+            );
+        });
+    }
+
+    pub type PrivateKey = <SigScheme as Scheme>::Private;
+
+    /// Commits the latest DKG task with a group key shared among the members, returning
+    /// the private key of the group and the share of every member along with its index.
+    pub fn commit_latest_dkg_task_with_keys(
+        controller: &mut Controller,
+    ) -> (PrivateKey, HashMap<String, (u32, PrivateKey)>) {
+        let dkg_task = (*controller.emit_dkg_task(0).last().unwrap()).clone();
+
+        let rng = &mut rand::thread_rng();
+
+        let private_poly = Poly::<PrivateKey>::new_from(dkg_task.threshold - 1, rng);
+
+        let public_poly = private_poly.commit::<G1>();
+
+        let private_key = *private_poly.public_key();
+
+        let public_key = *public_poly.public_key();
+
+        let mut member_keys = HashMap::new();
+
+        for (id_address, member_index) in dkg_task.members.iter() {
+            let member_private_key = private_poly.eval(member_index.dkg_index()).value;
+
+            let member_public_key = public_poly.eval(member_index.dkg_index()).value;
+
+            controller.commit_dkg(
+                id_address.clone(),
+                dkg_task.group_index,
+                dkg_task.epoch,
+                bincode::serialize(&public_key).unwrap(),
+                bincode::serialize(&member_public_key).unwrap(),
+                vec![],
+            );
+
+            member_keys.insert(
+                id_address.clone(),
+                (member_index.dkg_index(), member_private_key),
+            );
+        }
+
+        (private_key, member_keys)
+    }
+
+    pub fn sign_partially(
+        member_keys: &HashMap<String, (u32, PrivateKey)>,
+        message: &[u8],
+    ) -> HashMap<String, Vec<u8>> {
+        member_keys
+            .iter()
+            .map(|(id_address, (index, private_key))| {
+                let partial = Eval {
+                    value: SigScheme::sign(private_key, message).unwrap(),
+                    index: *index,
+                };
+
+                (id_address.clone(), bincode::serialize(&partial).unwrap())
+            })
+            .collect()
+    }
+
+    fn setup_active_group(n: usize) -> Controller {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, n);
+
+        commit_latest_dkg_task(&mut controller, vec![1]);
+
+        controller
+    }
+
+    #[test]
+    fn dkg_tasks_are_queued_in_emission_order() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 5);
+
+        // every join from the 3rd member on emits a task for the next epoch
+        let dkg_tasks = controller.emit_dkg_task(0);
+
+        assert_eq!(dkg_tasks.len(), 3);
+        assert_eq!(
+            dkg_tasks
+                .iter()
+                .map(|task| task.epoch.0)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!(dkg_tasks
+            .iter()
+            .enumerate()
+            .all(|(i, task)| task.index == i));
+
+        // consumers only get what they have not seen yet
+        let dkg_tasks = controller.emit_dkg_task(2);
+
+        assert_eq!(dkg_tasks.len(), 1);
+        assert_eq!(dkg_tasks[0].epoch, Epoch(3));
+        assert!(controller.emit_dkg_task(3).is_empty());
+    }
+
+    #[test]
+    fn dkg_tasks_are_filtered_by_node() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 4);
+
+        // 0x3 joined last so it is only part of the second task
+        let dkg_tasks = controller.emit_dkg_task_for_node("0x3", 0);
+
+        assert_eq!(dkg_tasks.len(), 1);
+        assert_eq!(dkg_tasks[0].epoch, Epoch(2));
+
+        assert_eq!(controller.emit_dkg_task_for_node("0x0", 0).len(), 2);
+        assert!(controller.emit_dkg_task_for_node("0x9", 0).is_empty());
+    }
+
+    #[test]
+    fn dkg_output_is_checked_against_the_task() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 3);
+
+        let dkg_task = (*controller.emit_dkg_task(0).last().unwrap()).clone();
+
+        let threshold = dkg_task.threshold;
+
+        assert_eq!(
+            check_dkg_output(&dkg_task, threshold, vec![0, 1, 2]),
+            Ok(())
+        );
+
+        // the DKG may disqualify members, but not qualify strangers
+        assert_eq!(check_dkg_output(&dkg_task, threshold, vec![0, 2]), Ok(()));
+        assert_eq!(
+            check_dkg_output(&dkg_task, threshold, vec![0, 1, 3]),
+            Err(DKGOutputError::UnknownQualifiedIndex(3))
+        );
+
+        assert_eq!(
+            check_dkg_output(&dkg_task, threshold + 1, vec![0, 1, 2]),
+            Err(DKGOutputError::ThresholdMismatch(threshold, threshold + 1))
+        );
+    }
+
+    #[test]
+    fn missed_dkg_tasks_are_backfilled() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 4);
+
+        // only the latest task of the group is still worth running
+        let backfill = controller.backfill_dkg_tasks("0x0");
+
+        assert_eq!(backfill.len(), 1);
+        assert_eq!(backfill[0].0.epoch, Epoch(2));
+        assert_eq!(backfill[0].1, DKGCatchUp::Join);
+
+        assert!(controller.backfill_dkg_tasks("0x9").is_empty());
+
+        controller.mine(DEFAULT_PHASE_DURATION + 1);
+
+        assert_eq!(
+            controller.backfill_dkg_tasks("0x0")[0].1,
+            DKGCatchUp::AwaitCheckDkgState
+        );
+
+        // nothing is left to do once the node committed
+        commit_latest_dkg_task(&mut controller, vec![1]);
+
+        assert!(controller.backfill_dkg_tasks("0x0").is_empty());
+    }
+
+    #[test]
+    fn dkg_transcript_is_kept_by_coordinator() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 3);
+
+        let dkg_task = (*controller.emit_dkg_task(0).last().unwrap()).clone();
+
+        let coordinator = controller
+            .get_coordinator_mut(dkg_task.group_index)
+            .unwrap();
+
+        assert!(coordinator.publish(String::from("0x1"), vec![1]));
+
+        controller.mine(DEFAULT_PHASE_DURATION + 1);
+
+        let coordinator = controller
+            .get_coordinator_mut(dkg_task.group_index)
+            .unwrap();
+
+        assert!(coordinator.publish(String::from("0x1"), vec![2]));
+
+        commit_latest_dkg_task(&mut controller, vec![1]);
+
+        // the ceremony data stays readable after the group is formed
+        let transcript = controller
+            .get_dkg_transcript(dkg_task.group_index, dkg_task.epoch)
+            .unwrap();
+
+        assert_eq!(transcript.participants, vec!["0x0", "0x1", "0x2"]);
+        assert_eq!(transcript.shares[1], vec![1]);
+        assert_eq!(transcript.responses[1], vec![2]);
+        assert!(transcript.justifications.iter().all(|j| j.is_empty()));
+
+        assert!(controller
+            .get_dkg_transcript(dkg_task.group_index, dkg_task.epoch.next())
+            .is_none());
+    }
+
+    #[test]
+    fn state_hash_is_stable() {
+        let controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        // pinned, nodes compare hashes across builds so a change here is a breaking one
+        assert_eq!(controller.state_hash(), 0xcbc1_5b3e_214f_9ceb);
+    }
+
+    #[test]
+    fn state_hash_flags_stale_group_snapshot() {
+        let mut controller = setup_active_group(5);
+
+        let snapshot = controller.get_group(GroupIndex(1)).clone();
+
+        assert_eq!(
+            controller.group_state_hash(GroupIndex(1)),
+            Some(snapshot.state_hash(controller.mixing))
+        );
+        assert_eq!(controller.group_state_hash(GroupIndex(2)), None);
+
+        let state_hash = controller.state_hash();
+
+        register_node(&mut controller, "0x5");
+
+        assert_ne!(
+            controller.group_state_hash(GroupIndex(1)),
+            Some(snapshot.state_hash(controller.mixing))
+        );
+        assert_ne!(controller.state_hash(), state_hash);
+    }
+
+    #[test]
+    fn check_dkg_state_archives_ended_coordinator() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 3);
+
+        let dkg_task = (*controller.emit_dkg_task(0).last().unwrap()).clone();
+
+        let coordinator = controller
+            .get_coordinator_mut(dkg_task.group_index)
+            .unwrap();
+
+        assert!(coordinator.publish(String::from("0x0"), vec![1]));
+
+        // the DKG is still running
+        assert!(!controller.check_dkg_state(String::from("0x0"), dkg_task.group_index));
+
+        controller.mine(3 * DEFAULT_PHASE_DURATION + 1);
+
+        assert!(!controller.check_dkg_state(String::from("0x9"), dkg_task.group_index));
+        assert!(controller.check_dkg_state(String::from("0x0"), dkg_task.group_index));
+        assert!(controller.get_coordinator(dkg_task.group_index).is_none());
+
+        let transcript = controller
+            .get_dkg_transcript(dkg_task.group_index, dkg_task.epoch)
+            .unwrap();
+
+        assert_eq!(transcript.shares[0], vec![1]);
+    }
+
+    #[test]
+    fn stuck_dkg_is_run_again_without_absent_members() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 5);
+
+        let dkg_task = (*controller.emit_dkg_task(0).last().unwrap()).clone();
+
+        // 2 identical commits don't reach the threshold of 3, and 2 members stay silent
+        for (id_address, public_key) in [("0x0", 1), ("0x1", 1), ("0x2", 2)].iter() {
+            assert!(controller.commit_dkg(
+                String::from(*id_address),
+                dkg_task.group_index,
+                dkg_task.epoch,
+                vec![*public_key],
+                vec![],
+                vec![],
+            ));
+        }
+
+        controller.mine(DEFAULT_DKG_COMMIT_DEADLINE - 1);
+
+        assert_eq!(controller.emit_dkg_task(dkg_task.index + 1).len(), 0);
+
+        controller.mine(1);
+
+        let dkg_tasks = controller.emit_dkg_task(dkg_task.index + 1);
+
+        assert_eq!(dkg_tasks.len(), 1);
+        assert_eq!(dkg_tasks[0].epoch, dkg_task.epoch.next());
+        assert_eq!(
+            dkg_tasks[0].members.keys().cloned().collect::<Vec<_>>(),
+            vec!["0x0", "0x1", "0x2"]
+        );
+
+        let group = controller.get_group(dkg_task.group_index);
+
+        assert_eq!(group.state, GroupState::AwaitingCommits);
+        assert!(group.commit_cache.is_empty());
+
+        // the silent members are slashed and frozen
+        for id_address in ["0x3", "0x4"].iter() {
+            let node = controller.get_node(id_address.to_string());
+
+            assert!(!node.state);
+            assert_eq!(node.staking, NODE_STAKING_AMOUNT - DKG_ABSENCE_PENALTY);
+            assert_eq!(
+                controller.get_reward_statement(id_address).unwrap().slashed,
+                DKG_ABSENCE_PENALTY
+            );
+        }
+    }
+
+    #[test]
+    fn stuck_rekeying_is_run_again() {
+        let mut controller = setup_active_group(5);
+
+        let public_key = controller.get_group(GroupIndex(1)).public_key.clone();
+
+        // the join re-keys the group, which keeps signing with its key meanwhile
+        register_node(&mut controller, "0x5");
+
+        let dkg_task = (*controller
+            .emit_dkg_task(0)
+            .into_iter()
+            .rev()
+            .find(|task| task.group_index == GroupIndex(1))
+            .unwrap())
+        .clone();
+
+        assert_eq!(
+            controller.get_group(GroupIndex(1)).pending_epoch,
+            Some(dkg_task.epoch)
+        );
+
+        controller.mine(DEFAULT_DKG_COMMIT_DEADLINE);
+
+        let dkg_tasks = controller
+            .emit_dkg_task(dkg_task.index + 1)
+            .into_iter()
+            .filter(|task| task.group_index == GroupIndex(1))
+            .collect::<Vec<_>>();
+
+        assert_eq!(dkg_tasks.len(), 1);
+        assert_eq!(dkg_tasks[0].epoch, dkg_task.epoch.next());
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.public_key, public_key);
+        assert_eq!(group.pending_epoch, Some(dkg_task.epoch.next()));
+    }
+
+    #[test]
+    fn archived_coordinators_are_bounded() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        controller.coordinator_retention = 2;
+
+        // every join from the 3rd member on replaces the coordinator of the group
+        register_nodes(&mut controller, 6);
+
+        assert_eq!(
+            controller.get_coordinator(GroupIndex(1)).unwrap().epoch,
+            Epoch(4)
+        );
+        assert!(controller
+            .get_archived_coordinator(GroupIndex(1), Epoch(1))
+            .is_none());
+        assert!(controller
+            .get_archived_coordinator(GroupIndex(1), Epoch(2))
+            .is_some());
+        assert!(controller
+            .get_archived_coordinator(GroupIndex(1), Epoch(3))
+            .is_some());
+    }
+
+    #[test]
+    fn signature_tasks_are_not_overwritten() {
+        let mut controller = setup_active_group(5);
+
+        assert!(request_randomness(&mut controller, "first"));
+        assert!(request_randomness(&mut controller, "second"));
+        assert!(request_randomness(&mut controller, "third"));
+
+        // a node polling after both requests still sees all of them, in order
+        let signature_tasks = controller.emit_signature_task(TaskIndex(0));
+
+        assert_eq!(
+            signature_tasks
+                .iter()
+                .map(|task| task.seed.as_slice())
+                .collect::<Vec<_>>(),
+            vec![&b"first"[..], b"second", b"third"]
+        );
+
+        // and pages from the last index it has handled
+        let cursor = TaskIndex(signature_tasks[1].index.0 + 1);
+
+        let signature_tasks = controller.emit_signature_task(cursor);
+
+        assert_eq!(signature_tasks.len(), 1);
+        assert_eq!(signature_tasks[0].seed, b"third");
+    }
+
+    #[test]
+    fn request_messages_bind_the_requester_and_block_height() {
+        let mut controller = setup_active_group(5);
+
+        assert!(request_randomness(&mut controller, "seed"));
+
+        controller.mine(1);
+
+        assert!(request_randomness(&mut controller, "seed"));
+
+        let tasks = controller.pending_signature_tasks();
+
+        assert_eq!(tasks[0].requester, REQUESTER);
+        assert_eq!(
+            tasks[0].message(),
+            request_message(b"seed", REQUESTER, tasks[0].request_block_height)
+        );
+
+        // the same seed requested again later gets another signature
+        assert_ne!(tasks[0].message(), tasks[1].message());
+
+        // a seed can't pass for another seed followed by part of the requester
+        assert_ne!(
+            request_message(b"ab", "c", 0),
+            request_message(b"a", "bc", 0)
+        );
+    }
+
+    #[test]
+    fn node_join_fills_groups_before_creating_new_ones() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 5);
+
+        // the first group is not formed yet, so everyone joins it
+        assert_eq!(controller.groups.len(), 1);
+        assert_eq!(controller.get_group(GroupIndex(1)).size, 5);
+    }
+
+    #[test]
+    fn node_join_rebalances_into_new_group() {
+        let mut controller = setup_active_group(5);
+
+        let dkg_task_count = controller.emit_dkg_task(0).len();
+
+        register_node(&mut controller, "0x5");
+
+        assert_eq!(controller.groups.len(), 2);
+
+        for group_index in 1..=2 {
+            let group = controller.get_group(GroupIndex(group_index));
+
+            assert_eq!(group.size, 3);
+            assert_eq!(group.members.len(), 3);
+
+            let mut indices = group
+                .members
+                .values()
+                .map(|member| member.index.0)
+                .collect::<Vec<_>>();
+            indices.sort_unstable();
+            assert_eq!(indices, vec![1, 2, 3]);
+        }
+
+        assert!(controller
+            .get_group(GroupIndex(2))
+            .members
+            .contains_key("0x5"));
+
+        // both groups run a new DKG, once each
+        let dkg_tasks = controller.emit_dkg_task(dkg_task_count);
+
+        assert_eq!(dkg_tasks.len(), 2);
+        assert_eq!(dkg_tasks[0].group_index, GroupIndex(1));
+        assert_eq!(dkg_tasks[1].group_index, GroupIndex(2));
+        assert_eq!(dkg_tasks[1].members.len(), 3);
+    }
+
+    #[test]
+    fn node_join_waits_when_no_member_is_spare() {
+        let mut controller = setup_active_group(3);
+
+        let dkg_task_count = controller.emit_dkg_task(0).len();
+
+        register_node(&mut controller, "0x3");
+
+        assert_eq!(controller.get_group(GroupIndex(1)).size, 3);
+        assert_eq!(controller.get_group(GroupIndex(2)).size, 1);
+        assert_eq!(controller.emit_dkg_task(dkg_task_count).len(), 0);
+    }
+
+    fn setup_groups(sizes: &[usize], policy: RebalanceFailurePolicy) -> Controller {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        controller.rebalance_failure_policy = policy;
+
+        let mut id = 0;
+
+        for &size in sizes {
+            let group_index = controller.add_group(GroupConfig::default());
+
+            for _ in 0..size {
+                let id_address = format!("0x{}", id);
+
+                controller.nodes.insert(
+                    id_address.clone(),
+                    Node {
+                        id_address: id_address.clone(),
+                        id_public_key: vec![],
+                        endpoint: String::from(""),
+                        reward_address: id_address.clone(),
+                        state: true,
+                        pending_until_block: 0,
+                        staking: 50000,
+                        capabilities: Capabilities::default(),
+                        last_active_block: 0,
+                    },
+                );
+
+                controller.rewards.insert(id_address.clone(), 0);
+
+                controller.add_to_group(&id_address, group_index, false);
+
+                id += 1;
+            }
+
+            let group = controller.groups.get_mut(&group_index).unwrap();
+
+            group.state = GroupState::Active;
+
+            group.key_threshold = group.threshold;
+        }
+
+        controller
+    }
+
+    #[test]
+    fn stake_weighted_grouping_fills_the_least_staked_group() {
+        let mut controller = setup_groups(&[5, 4, 4, 4, 4], RebalanceFailurePolicy::LeavePending);
+
+        // two members of the largest group got slashed, which leaves it the least staked
+        for id_address in ["0x0", "0x1"].iter() {
+            controller.nodes.get_mut(*id_address).unwrap().staking -= 30000;
+        }
+
+        assert_eq!(controller.find_or_create_available_group(), GroupIndex(2));
+
+        controller.grouping = Grouping::StakeWeighted;
+
+        assert_eq!(controller.find_or_create_available_group(), GroupIndex(1));
+    }
+
+    #[test]
+    fn node_join_scatters_when_no_member_is_spare() {
+        let mut controller = setup_active_group(3);
+
+        controller.rebalance_failure_policy = RebalanceFailurePolicy::Scatter;
+
+        let dkg_task_count = controller.emit_dkg_task(0).len();
+
+        register_node(&mut controller, "0x3");
+
+        assert_eq!(controller.get_group(GroupIndex(1)).size, 4);
+        assert_eq!(controller.get_group(GroupIndex(2)).size, 0);
+        assert!(controller
+            .get_group(GroupIndex(1))
+            .members
+            .contains_key("0x3"));
+
+        let dkg_tasks = controller.emit_dkg_task(dkg_task_count);
+
+        assert_eq!(dkg_tasks.len(), 1);
+        assert_eq!(dkg_tasks[0].group_index, GroupIndex(1));
+        assert_eq!(dkg_tasks[0].members.len(), 4);
+    }
+
+    #[test]
+    fn freeze_node_keeps_group_above_minimum() {
+        let mut controller = setup_groups(&[4], RebalanceFailurePolicy::LeavePending);
+
+        controller.freeze_node("0x0", 0);
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.size, 3);
+        assert_eq!(group.state, GroupState::Active);
+        assert!(!controller.get_node(String::from("0x0")).state);
+
+        let dkg_tasks = controller.emit_dkg_task(0);
+
+        assert_eq!(dkg_tasks.len(), 1);
+        assert_eq!(dkg_tasks[0].members.len(), 3);
+        assert!(!dkg_tasks[0].members.contains_key("0x0"));
+    }
+
+    #[test]
+    fn freeze_node_rebalances_undersized_group() {
+        let mut controller = setup_groups(&[3, 5], RebalanceFailurePolicy::LeavePending);
+
+        controller.freeze_node("0x0", 0);
+
+        assert_eq!(controller.get_group(GroupIndex(1)).size, 3);
+        assert_eq!(controller.get_group(GroupIndex(2)).size, 4);
+        // the 2 members left can't sign with the old key, the group waits for the new one
+        assert_eq!(
+            controller.get_group(GroupIndex(1)).state,
+            GroupState::AwaitingCommits
+        );
+
+        let group_indices = controller
+            .emit_dkg_task(0)
+            .iter()
+            .map(|task| task.group_index)
+            .collect::<Vec<_>>();
+
+        assert_eq!(group_indices, vec![GroupIndex(2), GroupIndex(1)]);
+    }
+
+    #[test]
+    fn group_states_only_go_forward() {
+        use GroupState::*;
+
+        assert!(Forming.can_become(AwaitingCommits));
+        assert!(!Forming.can_become(Active));
+        assert!(!Degraded.can_become(Active));
+        assert!(!Disbanded.can_become(AwaitingCommits));
+        assert!([Forming, AwaitingCommits, Active, Degraded]
+            .iter()
+            .all(|state| state.can_become(Disbanded)));
+
+        let controller = setup_groups(&[4], RebalanceFailurePolicy::LeavePending);
+
+        assert_eq!(controller.get_group_state(GroupIndex(1)), Some(Active));
+        assert_eq!(controller.get_group_state(GroupIndex(2)), None);
+    }
+
+    #[test]
+    fn blacklisted_nodes_are_frozen_out_and_cannot_register() {
+        let mut controller = setup_groups(&[4], RebalanceFailurePolicy::LeavePending);
+
+        assert!(!controller.blacklist_node(String::from("0x1"), String::from("0x0")));
+        assert!(controller.blacklist_node(String::from(OWNER), String::from("0x0")));
+        assert!(controller.blacklist_node(String::from(OWNER), String::from("0x9")));
+        assert!(!controller.blacklist_node(String::from(OWNER), String::from("0x9")));
+
+        assert!(!controller.get_node(String::from("0x0")).state);
+        assert!(!controller
+            .get_group(GroupIndex(1))
+            .members
+            .contains_key("0x0"));
+        assert!(!register_node(&mut controller, "0x9"));
+        assert_eq!(controller.get_blacklisted_nodes(), vec!["0x0", "0x9"]);
+
+        assert!(controller.unblacklist_node(String::from(OWNER), String::from("0x9")));
+        assert!(!controller.unblacklist_node(String::from(OWNER), String::from("0x9")));
+        assert!(register_node(&mut controller, "0x9"));
+
+        // a frozen node comes back once it is no longer blacklisted
+        assert!(!controller.node_activate(String::from("0x0")));
+        assert!(controller.unblacklist_node(String::from(OWNER), String::from("0x0")));
+        assert!(controller.node_activate(String::from("0x0")));
+        assert!(!controller.node_activate(String::from("0x0")));
+
+        assert!(controller.get_node(String::from("0x0")).state);
+        assert!(controller
+            .groups
+            .values()
+            .any(|group| group.members.contains_key("0x0")));
+        assert!(controller.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn unresponsive_nodes_are_frozen() {
+        let mut controller = setup_groups(&[4], RebalanceFailurePolicy::LeavePending);
+
+        controller.liveness_timeout = Some(10);
+
+        controller.mine(5);
+
+        for id_address in ["0x0", "0x1", "0x2"].iter() {
+            assert!(controller.node_heartbeat(String::from(*id_address)));
+        }
+
+        assert!(!controller.node_heartbeat(String::from("0x9")));
+
+        controller.mine(5);
+
+        assert!(!controller.get_node(String::from("0x3")).state);
+        assert_eq!(controller.get_group(GroupIndex(1)).size, 3);
+        assert!(["0x0", "0x1", "0x2"]
+            .iter()
+            .all(|id_address| controller.get_node(String::from(*id_address)).state));
+        assert!(!controller.node_heartbeat(String::from("0x3")));
+
+        // back from its downtime, the node gets a full timeout again
+        assert!(controller.node_activate(String::from("0x3")));
+
+        controller.mine(1);
+
+        assert!(controller.get_node(String::from("0x3")).state);
+        assert!(controller.node_heartbeat(String::from("0x3")));
+        assert!(controller.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn freeze_node_leaves_group_pending() {
+        let mut controller = setup_groups(&[3, 3], RebalanceFailurePolicy::LeavePending);
+
+        controller.freeze_node("0x0", 0);
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.size, 2);
+        assert_eq!(group.state, GroupState::Degraded);
+        assert_eq!(controller.get_group(GroupIndex(2)).size, 3);
+        assert!(controller.emit_dkg_task(0).is_empty());
+        assert_eq!(controller.valid_group_indices(), vec![GroupIndex(2)]);
+    }
+
+    #[test]
+    fn freeze_node_scatters_group() {
+        let mut controller = setup_groups(&[3, 3, 3], RebalanceFailurePolicy::Scatter);
+
+        controller.freeze_node("0x0", 0);
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.size, 0);
+        assert_eq!(group.state, GroupState::Degraded);
+        // each member goes to the smallest group at the time
+        assert_eq!(controller.get_group(GroupIndex(2)).size, 4);
+        assert_eq!(controller.get_group(GroupIndex(3)).size, 4);
+
+        let group_indices = controller
+            .emit_dkg_task(0)
+            .iter()
+            .map(|task| task.group_index)
+            .collect::<Vec<_>>();
+
+        assert_eq!(group_indices, vec![GroupIndex(2), GroupIndex(3)]);
+
+        // the emptied group doesn't take every joiner from now on
+        assert!(controller.get_group(GroupIndex(1)).scattered);
+
+        register_node(&mut controller, "0x9");
+
+        assert_eq!(controller.get_group(GroupIndex(1)).size, 0);
+        assert_eq!(controller.get_group(GroupIndex(2)).size, 5);
+    }
+
+    fn commit_dkg_task_with_disqualified(
+        controller: &mut Controller,
+        dkg_task: &DKGTask,
+        public_key: Vec<u8>,
+        disqualified_nodes: Vec<String>,
+    ) {
+        let mut committers = dkg_task.members.keys().cloned().collect::<Vec<_>>();
+        committers.sort();
+
+        for id_address in committers {
+            controller.commit_dkg(
+                id_address,
+                dkg_task.group_index,
+                dkg_task.epoch,
+                public_key.clone(),
+                vec![],
+                disqualified_nodes.clone(),
+            );
+        }
+    }
+
+    #[test]
+    fn commit_dkg_chooses_committers_from_retained_members() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 5);
+
+        let dkg_task = (*controller.emit_dkg_task(0).last().unwrap()).clone();
+
+        commit_dkg_task_with_disqualified(
+            &mut controller,
+            &dkg_task,
+            vec![1],
+            vec![String::from("0x4")],
+        );
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.size, 4);
+        assert_eq!(group.threshold, 3);
+        assert!(!group.members.contains_key("0x4"));
+        assert_eq!(group.committers.len(), 3);
+        assert!(group
+            .committers
+            .iter()
+            .all(|committer| group.members.contains_key(committer)));
+    }
+
+    #[test]
+    fn commit_dkg_reruns_dkg_when_threshold_is_unreachable() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 8);
+
+        let dkg_task = (*controller.emit_dkg_task(0).last().unwrap()).clone();
+
+        assert_eq!(dkg_task.threshold, 5);
+
+        // not the last members, whose indices the retained ones move into
+        let disqualified_nodes = (0..4).map(|i| format!("0x{}", 2 * i)).collect::<Vec<_>>();
+
+        commit_dkg_task_with_disqualified(&mut controller, &dkg_task, vec![1], disqualified_nodes);
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.state, GroupState::AwaitingCommits);
+        assert!(group.committers.is_empty());
+        assert_eq!(group.size, 4);
+        assert_eq!(group.threshold, 3);
+
+        // the retained members get a new task with a threshold they can reach
+        let rerun_task = controller.emit_dkg_task(dkg_task.index + 1)[0];
+
+        assert_eq!(rerun_task.epoch, dkg_task.epoch.next());
+        assert_eq!(rerun_task.size, 4);
+        assert_eq!(rerun_task.threshold, 3);
+
+        let mut member_indices = rerun_task
+            .members
+            .values()
+            .map(|index| index.0)
+            .collect::<Vec<_>>();
+
+        member_indices.sort();
+
+        assert_eq!(member_indices, vec![1, 2, 3, 4]);
+        assert!(controller.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn commit_dkg_deactivates_group_below_minimum() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 5);
+
+        let dkg_task = (*controller.emit_dkg_task(0).last().unwrap()).clone();
+
+        let disqualified_nodes = (2..5).map(|i| format!("0x{}", i)).collect::<Vec<_>>();
+
+        commit_dkg_task_with_disqualified(&mut controller, &dkg_task, vec![1], disqualified_nodes);
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.state, GroupState::Forming);
+        assert_eq!(group.size, 2);
+        assert!(group.committers.is_empty());
+        assert!(controller.valid_group_indices().is_empty());
+    }
+
+    #[test]
+    fn commit_dkg_replaces_key_and_committers_of_previous_epoch() {
+        let mut controller = setup_active_group(5);
+
+        register_node(&mut controller, "0x5");
+
+        let dkg_task = (*controller
+            .emit_dkg_task(0)
+            .iter()
+            .rev()
+            .find(|task| task.group_index == GroupIndex(1))
+            .unwrap())
+        .clone();
+
+        commit_dkg_task_with_disqualified(&mut controller, &dkg_task, vec![3], vec![]);
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.epoch, dkg_task.epoch);
+        assert_eq!(group.public_key, vec![3]);
+        assert_eq!(group.committers.len(), 3);
+        assert!(group
+            .committers
+            .iter()
+            .all(|committer| group.members.contains_key(committer)));
+    }
+
+    #[test]
+    fn disband_group_requires_owner() {
+        let mut controller = setup_active_group(5);
+
+        assert!(!controller.disband_group(String::from("0x1"), GroupIndex(1)));
+        assert!(!controller.disband_group(String::from(OWNER), GroupIndex(2)));
+        assert_eq!(
+            controller.get_group(GroupIndex(1)).state,
+            GroupState::Active
+        );
+    }
+
+    #[test]
+    fn group_config_is_validated_and_owner_only() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        let config = GroupConfig {
+            minimum_threshold: 4,
+            capacity: 6,
+        };
+
+        assert!(!controller.set_group_config(String::from("0x1"), config));
+
+        for invalid in [
+            GroupConfig {
+                minimum_threshold: 1,
+                capacity: 6,
+            },
+            GroupConfig {
+                minimum_threshold: 7,
+                capacity: 6,
+            },
+        ]
+        .iter()
+        {
+            assert!(invalid.validate().is_err());
+            assert!(!controller.set_group_config(String::from(OWNER), *invalid));
+            assert!(!controller.create_group(String::from(OWNER), *invalid));
+        }
+
+        assert_eq!(controller.get_group_config(), &GroupConfig::default());
+
+        assert!(controller.set_group_config(String::from(OWNER), config));
+        assert_eq!(controller.get_group_config(), &config);
+    }
+
+    #[test]
+    fn group_of_two_commits_with_both_members() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        let config = GroupConfig {
+            minimum_threshold: 2,
+            capacity: 2,
+        };
+
+        assert!(controller.create_group(String::from(OWNER), config));
+
+        register_nodes(&mut controller, 2);
+
+        commit_latest_dkg_task_with_keys(&mut controller);
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.key_threshold, 2);
+
+        let mut committers = group.committers.clone();
+
+        committers.sort();
+
+        assert_eq!(committers, vec![String::from("0x0"), String::from("0x1")]);
+    }
+
+    #[test]
+    fn created_group_keeps_its_own_sizing() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        let config = GroupConfig {
+            minimum_threshold: 4,
+            capacity: 6,
+        };
+
+        assert!(controller.create_group(String::from(OWNER), config));
+
+        register_nodes(&mut controller, 3);
+
+        // below its own minimum the group doesn't run a DKG yet
+        assert!(controller.emit_dkg_task(0).is_empty());
+
+        (3..6).for_each(|i| {
+            register_node(&mut controller, &format!("0x{}", i));
+        });
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.size, 6);
+        assert_eq!(group.threshold, 4);
+
+        let dkg_task = controller.emit_dkg_task(0).pop().unwrap();
+
+        assert_eq!(dkg_task.size, 6);
+        assert_eq!(dkg_task.threshold, 4);
+
+        register_node(&mut controller, "0x6");
+
+        // the group is full, the next node goes to a group of the default sizing which
+        // borrows members down to the minimum of the full group, not the default one
+        assert_eq!(controller.get_group(GroupIndex(1)).size, 4);
+
+        let group = controller.get_group(GroupIndex(2));
+
+        assert_eq!(group.size, 3);
+        assert_eq!(group.capacity, GROUP_MAX_CAPACITY);
+        assert_eq!(group.minimum_threshold, DEFAULT_MINIMUM_THRESHOLD);
+    }
+
+    #[test]
+    fn disband_group_regroups_members() {
+        let mut controller = setup_active_group(5);
+
+        let epoch_before = controller.get_group(GroupIndex(1)).epoch;
+
+        assert!(controller.disband_group(String::from(OWNER), GroupIndex(1)));
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.state, GroupState::AwaitingCommits);
+        assert!(group.committers.is_empty());
+        assert!(group.public_key.is_empty());
+        assert_eq!(group.size, 5);
+        assert!(group.epoch > epoch_before);
+
+        let dkg_task = controller.emit_dkg_task(0).pop().unwrap();
+
+        assert_eq!(dkg_task.epoch, group.epoch);
+        assert_eq!(dkg_task.members.len(), 5);
+
+        commit_latest_dkg_task(&mut controller, vec![2]);
+
+        let group = controller.get_group(GroupIndex(1));
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.public_key, vec![2]);
+    }
+
+    #[test]
+    fn identical_transactions_form_identical_groups() {
+        let form_groups = || {
+            let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+            register_nodes(&mut controller, 23);
+
+            controller.disband_group(String::from(OWNER), GroupIndex(2));
+
+            commit_latest_dkg_task(&mut controller, vec![1]);
+
+            controller
+        };
+
+        let (a, b) = (form_groups(), form_groups());
+
+        assert_eq!(a.group_members(), b.group_members());
+        assert_eq!(a.valid_group_indices(), b.valid_group_indices());
+        assert_eq!(
+            a.emit_dkg_task(0)
+                .iter()
+                .map(|task| (task.group_index, task.epoch, task.members.clone()))
+                .collect::<Vec<_>>(),
+            b.emit_dkg_task(0)
+                .iter()
+                .map(|task| (task.group_index, task.epoch, task.members.clone()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(a.state_hash(), b.state_hash());
+    }
+
+    #[test]
+    fn disband_group_keeps_in_flight_signature_tasks() {
+        let mut controller = setup_active_group(5);
+
+        assert!(request_randomness(&mut controller, "in-flight"));
+
+        let signature_index = controller.emit_signature_task(TaskIndex(0))[0].index;
+
+        assert!(controller.disband_group(String::from(OWNER), GroupIndex(1)));
+
+        // no valid group is left, so the task waits for the group to be formed again
+        assert!(controller.valid_group_indices().is_empty());
+        assert_eq!(controller.pending_signature_tasks().len(), 1);
+        assert!(!controller.fulfill(String::from("0x0"), signature_index, vec![], HashMap::new()));
+
+        commit_latest_dkg_task(&mut controller, vec![2]);
+
+        let signature_task = controller.pending_signature_tasks()[0];
+
+        assert_eq!(signature_task.index, signature_index);
+        assert_eq!(signature_task.group_index, GroupIndex(1));
+        assert_eq!(
+            controller.get_group(GroupIndex(1)).state,
+            GroupState::Active
+        );
+    }
+
+    #[test]
+    fn reassigned_requests_keep_their_message() {
+        let mut controller = setup_groups(&[5, 5], RebalanceFailurePolicy::LeavePending);
+
+        assert!(request_randomness(&mut controller, "reassigned"));
+
+        let task = controller.emit_signature_task(TaskIndex(0))[0].clone();
+
+        controller.mine(3);
+
+        assert!(controller.disband_group(String::from(OWNER), task.group_index));
+
+        let reassigned = controller.pending_signature_tasks()[0].clone();
+
+        assert_ne!(reassigned.group_index, task.group_index);
+        assert_eq!(
+            reassigned.assignment_block_height,
+            task.assignment_block_height + 3
+        );
+        assert_eq!(reassigned.request_block_height, task.request_block_height);
+        assert_eq!(reassigned.message(), task.message());
+    }
+
+    #[test]
+    fn beacon_rounds_are_chained() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 3);
+
+        let (private_key, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        controller.beacon_period = Some(5);
+
+        controller.mine(4);
+
+        assert!(controller.pending_signature_tasks().is_empty());
+
+        controller.mine(1);
+
+        let task = controller.pending_signature_tasks()[0].clone();
+
+        assert_eq!(task.beacon_round, Some(1));
+        assert_eq!(task.seed, beacon_message(1, None));
+
+        // the chain has fallen behind, the next round waits for this one
+        controller.mine(10);
+
+        assert_eq!(controller.pending_signature_tasks().len(), 1);
+
+        let committer = controller.get_group(GroupIndex(1)).committers[0].clone();
+
+        let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+        let partial_signatures = sign_partially(&member_keys, &task.message());
+
+        assert!(controller.fulfill(committer, task.index, signature.clone(), partial_signatures));
+
+        assert_eq!(controller.latest_beacon_round(), 1);
+        assert_eq!(controller.get_beacon(1), Some(&signature));
+
+        let task = controller.pending_signature_tasks()[0];
+
+        assert_eq!(task.beacon_round, Some(2));
+        assert_eq!(task.seed, beacon_message(2, Some(&signature)));
+    }
+
+    #[test]
+    fn oversized_fulfillments_are_rejected() {
+        let partial_signatures = (0..4)
+            .map(|i| (format!("0x{}", i), vec![0; partial_signature_length()]))
+            .collect::<HashMap<_, _>>();
+
+        let signature = vec![0; signature_length()];
+
+        assert_eq!(
+            check_fulfillment_sizes(4, &signature, &partial_signatures),
+            Ok(())
+        );
+        assert_eq!(
+            check_fulfillment_sizes(3, &signature, &partial_signatures),
+            Err(FulfillmentError::TooManyPartialSignatures(4, 3))
+        );
+        assert_eq!(
+            check_fulfillment_sizes(4, &signature[1..], &partial_signatures),
+            Err(FulfillmentError::InvalidSignatureLength(
+                signature_length() - 1,
+                signature_length()
+            ))
+        );
+
+        let mut partial_signatures = partial_signatures;
+
+        partial_signatures.insert(String::from("0x0"), vec![0; 1 << 20]);
+
+        assert!(matches!(
+            check_fulfillment_sizes(4, &signature, &partial_signatures),
+            Err(FulfillmentError::InvalidPartialSignatureLength(
+                _,
+                1_048_576,
+                _
+            ))
+        ));
+    }
+
+    #[test]
+    fn fulfillments_need_threshold_valid_partial_signatures() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 5);
+
+        let (private_key, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        assert!(request_randomness(&mut controller, "partials"));
+
+        let task = controller.emit_signature_task(TaskIndex(0))[0].clone();
+
+        let group = controller.get_group(GroupIndex(1)).clone();
+
+        let committer = group.committers[0].clone();
+
+        let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+        let mut signers = member_keys.keys().cloned().collect::<Vec<_>>();
+
+        signers.sort();
+
+        let signed_by = |signers: &[String]| {
+            let keys = member_keys
+                .iter()
+                .filter(|(id_address, _)| signers.contains(id_address))
+                .map(|(id_address, keys)| (id_address.clone(), *keys))
+                .collect::<HashMap<_, _>>();
+
+            sign_partially(&keys, &task.message())
+        };
+
+        let check = |partial_signatures: &HashMap<String, Vec<u8>>| {
+            check_partial_signatures(
+                &task.eligible_members,
+                group.key_threshold,
+                &signature,
+                partial_signatures,
+                &mut LagrangeCache::new(1),
+            )
+        };
+
+        let too_few = signed_by(&signers[..group.key_threshold - 1]);
+
+        assert_eq!(
+            check(&too_few),
+            Err(FulfillmentError::NotEnoughPartialSignatures(
+                group.key_threshold - 1,
+                group.key_threshold
+            ))
+        );
+        assert!(!controller.fulfill(committer.clone(), task.index, signature.clone(), too_few));
+
+        // a non-member signing the message pads the list up to the threshold
+        let mut padded = signed_by(&signers[..group.key_threshold - 1]);
+
+        let mut outsider_keys = HashMap::new();
+
+        let (outsider_private_key, _) = SigScheme::keypair(&mut rand::thread_rng());
+
+        outsider_keys.insert(String::from("0xoutsider"), (0, outsider_private_key));
+
+        padded.extend(sign_partially(&outsider_keys, &task.message()));
+
+        assert_eq!(
+            check(&padded),
+            Err(FulfillmentError::NotAMember(String::from("0xoutsider")))
+        );
+        assert!(!controller.fulfill(committer.clone(), task.index, signature.clone(), padded));
+
+        // a member listed without having signed the message
+        let mut forged = signed_by(&signers[..group.key_threshold]);
+
+        let forger = signers[0].clone();
+
+        let forged_partial = Eval {
+            value: SigScheme::sign(&member_keys[&forger].1, b"another message").unwrap(),
+            index: member_keys[&forger].0,
+        };
+
+        forged.insert(forger.clone(), bincode::serialize(&forged_partial).unwrap());
+
+        assert_eq!(check(&forged), Err(FulfillmentError::SignatureMismatch));
+        assert!(!controller.fulfill(committer.clone(), task.index, signature.clone(), forged));
+
+        // a valid partial signature passed off as the one of another member
+        let mut relabeled = signed_by(&signers[..group.key_threshold]);
+
+        let (impersonated, impostor) = (signers[0].clone(), signers[group.key_threshold].clone());
+
+        let impostor_partial = signed_by(&signers[group.key_threshold..=group.key_threshold]);
+
+        relabeled.insert(impersonated.clone(), impostor_partial[&impostor].clone());
+
+        assert_eq!(
+            check(&relabeled),
+            Err(FulfillmentError::ShareIndexMismatch(
+                impersonated,
+                member_keys[&impostor].0
+            ))
+        );
+        assert!(!controller.fulfill(committer.clone(), task.index, signature.clone(), relabeled));
+
+        let partial_signatures = signed_by(&signers[..group.key_threshold]);
+
+        assert!(controller.fulfill(committer, task.index, signature, partial_signatures));
+        assert!(controller.pending_signature_tasks().is_empty());
+    }
+
+    #[test]
+    fn fulfillments_use_the_threshold_of_the_key() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 6);
+
+        let (private_key, mut member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        assert_eq!(controller.get_group(GroupIndex(1)).key_threshold, 4);
+
+        let leaver = controller
+            .get_group(GroupIndex(1))
+            .members
+            .keys()
+            .find(|id_address| {
+                !controller
+                    .get_group(GroupIndex(1))
+                    .committers
+                    .contains(id_address)
+            })
+            .unwrap()
+            .clone();
+
+        controller.freeze_node(&leaver, 0);
+
+        member_keys.remove(&leaver);
+
+        // the group signs with its key until the re-key commits, while the next DKG runs
+        // with the threshold of the smaller group
+        let group = controller.get_group(GroupIndex(1)).clone();
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.threshold, 3);
+        assert_eq!(group.key_threshold, 4);
+
+        assert!(request_randomness(&mut controller, "after a leave"));
+
+        let task = controller.emit_signature_task(TaskIndex(0))[0].clone();
+
+        let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+        let partial_signatures = sign_partially(&member_keys, &task.message());
+
+        assert!(controller.fulfill(
+            group.committers[0].clone(),
+            task.index,
+            signature,
+            partial_signatures
+        ));
+        assert!(controller.pending_signature_tasks().is_empty());
+    }
+
+    #[test]
+    fn member_keys_are_available_once_the_group_is_active() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 5);
+
+        let epoch = controller.get_group(GroupIndex(1)).epoch;
+
+        assert!(controller
+            .get_group_member_keys(GroupIndex(1), epoch)
+            .is_none());
+
+        let (_, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        assert!(controller
+            .get_group_member_keys(GroupIndex(1), epoch.next())
+            .is_none());
+
+        let keys = controller
+            .get_group_member_keys(GroupIndex(1), epoch)
+            .unwrap();
+
+        assert_eq!(keys.len(), member_keys.len());
+
+        // the keys verify the partial signatures of their members
+        let partial_signatures = sign_partially(&member_keys, b"late");
+
+        for (id_address, partial_public_key) in keys {
+            let member = EligibleMember {
+                reward_address: id_address.clone(),
+                partial_public_key,
+                share_index: Some(member_keys[&id_address].0),
+            };
+
+            assert!(verify_partial_signature(
+                &member,
+                b"late",
+                &partial_signatures[&id_address]
+            ));
+        }
+    }
+
+    #[test]
+    fn members_leaving_after_assignment_are_still_paid() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 5);
+
+        let (private_key, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        assert!(request_randomness(&mut controller, "snapshot"));
+
+        let task = controller.emit_signature_task(TaskIndex(0))[0].clone();
+
+        assert_eq!(task.eligible_members.len(), 5);
+
+        let committer = controller.get_group(GroupIndex(1)).committers[0].clone();
+
+        let leaver = member_keys
+            .keys()
+            .find(|id_address| {
+                !controller
+                    .get_group(GroupIndex(1))
+                    .committers
+                    .contains(id_address)
+            })
+            .unwrap()
+            .clone();
+
+        controller.freeze_node(&leaver, 0);
+
+        assert!(!controller
+            .get_group(GroupIndex(1))
+            .members
+            .contains_key(&leaver));
+
+        let reward_before = controller.rewards[&leaver];
+
+        let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+        let partial_signatures = sign_partially(&member_keys, &task.message());
+
+        assert!(controller.fulfill(committer, task.index, signature, partial_signatures));
+        assert_eq!(
+            controller.rewards[&leaver],
+            reward_before + REWARD_PER_SIGNATURE + task.fee * MEMBERS_FEE_SHARE / 100 / 5
+        );
+    }
+
+    #[test]
+    fn fulfillments_are_recorded_on_reward_statements() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 3);
+
+        let (private_key, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        assert!(request_randomness(&mut controller, "statement"));
+
+        let task = controller.emit_signature_task(TaskIndex(0))[0].clone();
+
+        let group_epoch = controller.get_group(GroupIndex(1)).epoch;
+
+        let committer = controller.get_group(GroupIndex(1)).committers[0].clone();
+
+        let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+        let partial_signatures = sign_partially(&member_keys, &task.message());
+
+        assert!(controller.get_reward_statement(&committer).is_none());
+        assert!(controller.fulfill(committer.clone(), task.index, signature, partial_signatures));
+
+        let statement = controller.get_reward_statement(&committer).unwrap();
+
+        let committer_fee = task.fee * COMMITTER_FEE_SHARE / 100;
+
+        let member_fee = task.fee * MEMBERS_FEE_SHARE / 100 / 3;
+
+        assert_eq!(
+            statement.earned,
+            COMMITTER_REWARD_PER_SIGNATURE + REWARD_PER_SIGNATURE + committer_fee + member_fee
+        );
+        assert_eq!(
+            statement.earned_from(RewardSource::Committer),
+            COMMITTER_REWARD_PER_SIGNATURE + committer_fee
+        );
+        assert_eq!(
+            statement.earned_by_epoch[&(group_epoch, RewardSource::Signature)],
+            REWARD_PER_SIGNATURE + member_fee
+        );
+        assert_eq!(statement.claimed, 0);
+        assert_eq!(statement.slashed, 0);
+        assert_eq!(controller.rewards[&committer], statement.earned);
+    }
+
+    #[test]
+    fn participation_is_tracked_and_weights_the_members_fee() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 4);
+
+        let (private_key, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        let fulfill = |controller: &mut Controller, message: &str, signers: &[&str]| {
+            assert!(request_randomness(controller, message));
+
+            let task = (*controller.emit_signature_task(TaskIndex(0)).last().unwrap()).clone();
+
+            let committer = controller.get_group(GroupIndex(1)).committers[0].clone();
+
+            let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+            let mut partial_signatures = sign_partially(&member_keys, &task.message());
+
+            partial_signatures.retain(|id_address, _| signers.contains(&id_address.as_str()));
+
+            assert!(controller.fulfill(committer, task.index, signature, partial_signatures));
+
+            task.fee
+        };
+
+        fulfill(&mut controller, "first", &["0x0", "0x1", "0x2"]);
+
+        let stats = *controller.get_participation_stats("0x3").unwrap();
+
+        assert_eq!(stats.partial_signatures, 0);
+        assert_eq!(stats.missed_tasks, 1);
+        assert_eq!(stats.dkg_commits, 1);
+
+        controller.participation_weighting = true;
+
+        let earned = |controller: &Controller, id_address: &str| {
+            controller
+                .get_reward_statement(id_address)
+                .map_or(0, |statement| {
+                    statement.earned_from(RewardSource::Signature)
+                })
+        };
+
+        let before = ["0x0", "0x3"]
+            .iter()
+            .map(|id_address| earned(&controller, id_address))
+            .collect::<Vec<_>>();
+
+        let fee = fulfill(&mut controller, "second", &["0x0", "0x1", "0x2", "0x3"]);
+
+        // 0x3 signed 1 of 2 tasks, the others all of them
+        assert_eq!(
+            controller
+                .get_participation_stats("0x3")
+                .unwrap()
+                .signing_rate(),
+            50
+        );
+
+        let members_fee = fee * MEMBERS_FEE_SHARE / 100;
+
+        assert_eq!(
+            earned(&controller, "0x0") - before[0],
+            REWARD_PER_SIGNATURE + members_fee * 100 / 350
+        );
+        assert_eq!(
+            earned(&controller, "0x3") - before[1],
+            REWARD_PER_SIGNATURE + members_fee * 50 / 350
+        );
+    }
+
+    #[test]
+    fn members_fee_is_split_equally_when_no_signer_has_weight() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 3);
+
+        let (private_key, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        controller.participation_weighting = true;
+
+        // every member signed well under 1% of its tasks
+        for stats in controller.participation.values_mut() {
+            stats.missed_tasks = 1000;
+        }
+
+        assert!(request_randomness(&mut controller, "rare"));
+
+        let task = (*controller.emit_signature_task(TaskIndex(0)).last().unwrap()).clone();
+
+        let committer = controller.get_group(GroupIndex(1)).committers[0].clone();
+
+        let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+        let partial_signatures = sign_partially(&member_keys, &task.message());
+
+        assert!(controller.fulfill(committer, task.index, signature, partial_signatures));
+
+        let members_fee = task.fee * MEMBERS_FEE_SHARE / 100;
+
+        for id_address in &["0x0", "0x1", "0x2"] {
+            assert_eq!(
+                controller
+                    .get_reward_statement(id_address)
+                    .unwrap()
+                    .earned_from(RewardSource::Signature),
+                REWARD_PER_SIGNATURE + members_fee / 3
+            );
+        }
+    }
+
+    #[test]
+    fn request_fees_are_split_on_fulfillment() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 3);
+
+        let (private_key, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        // the requester didn't approve the fee
+        controller.token.mint("0xfree-rider", request_fee(3));
+
+        assert!(!controller.request(String::from("0xfree-rider"), b"free".to_vec(), 3));
+        assert!(controller.pending_signature_tasks().is_empty());
+
+        controller
+            .token
+            .approve("0xfree-rider", CONTROLLER_ADDRESS, request_fee(3));
+
+        assert!(controller.request(String::from("0xfree-rider"), b"paid".to_vec(), 3));
+        assert_eq!(controller.token.balance_of("0xfree-rider"), 0);
+
+        let task = controller.emit_signature_task(TaskIndex(0))[0].clone();
+
+        assert_eq!(task.fee, REQUEST_FEE + 3 * REQUEST_FEE_PER_WORD);
+
+        let committer = controller.get_group(GroupIndex(1)).committers[0].clone();
+
+        let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+        let partial_signatures = sign_partially(&member_keys, &task.message());
+
+        let rewards_before = controller.rewards.values().sum::<usize>();
+
+        assert!(controller.fulfill(committer, task.index, signature, partial_signatures));
+
+        let fixed_rewards = COMMITTER_REWARD_PER_SIGNATURE + 3 * REWARD_PER_SIGNATURE;
+
+        let paid_fees = controller.rewards.values().sum::<usize>() - rewards_before - fixed_rewards;
+
+        // the fee is entirely split between the nodes and the treasury
+        assert_eq!(paid_fees + controller.get_treasury(), task.fee);
+        assert!(
+            controller.get_treasury()
+                >= task.fee * (100 - COMMITTER_FEE_SHARE - MEMBERS_FEE_SHARE) / 100
+        );
+    }
+
+    #[test]
+    fn stakes_and_claims_move_tokens() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        controller.token.mint("0x9", NODE_STAKING_AMOUNT);
+
+        // the controller wasn't approved to pull the stake
+        assert!(!controller.node_register(
+            String::from("0x9"),
+            vec![],
+            String::from(""),
+            String::from("0x9"),
+            Capabilities::default(),
+        ));
+
+        register_nodes(&mut controller, 3);
+
+        assert_eq!(controller.token.balance_of("0x1"), 0);
+        assert_eq!(
+            controller.token.balance_of(CONTROLLER_ADDRESS),
+            3 * NODE_STAKING_AMOUNT
+        );
+
+        let (private_key, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        assert!(request_randomness(&mut controller, "claim"));
+
+        let task = controller.emit_signature_task(TaskIndex(0))[0].clone();
+
+        let committer = controller.get_group(GroupIndex(1)).committers[0].clone();
+
+        let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+        let partial_signatures = sign_partially(&member_keys, &task.message());
+
+        assert!(controller.fulfill(committer.clone(), task.index, signature, partial_signatures));
+
+        let reward = controller.rewards[&committer];
+
+        assert!(controller.claim(committer.clone()));
+        assert!(!controller.claim(committer.clone()));
+
+        assert_eq!(controller.token.balance_of(&committer), reward);
+        assert_eq!(controller.rewards[&committer], 0);
+        assert_eq!(
+            controller.get_reward_statement(&committer).unwrap().claimed,
+            reward
+        );
+    }
+
+    #[test]
+    fn update_node_key_requires_both_keys_and_reruns_dkg() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        let rng = &mut rand::thread_rng();
+
+        let (private_key, public_key) = SigScheme::keypair(rng);
+
+        let old_key = bincode::serialize(&public_key).unwrap();
+
+        fund_stake(&mut controller, "0x0");
+
+        controller.node_register(
+            String::from("0x0"),
+            old_key.clone(),
+            String::from(""),
+            String::from("0x0"),
+            Capabilities::default(),
+        );
+
+        (1..3).for_each(|i| {
+            register_node(&mut controller, &format!("0x{}", i));
+        });
+
+        commit_latest_dkg_task(&mut controller, vec![1]);
+
+        let epoch = controller.get_group(GroupIndex(1)).epoch;
+
+        let (new_private_key, new_public_key) = SigScheme::keypair(rng);
+
+        let new_key = bincode::serialize(&new_public_key).unwrap();
+
+        let message = node_key_update_message("0x0", &old_key, &new_key);
+
+        let signature = SigScheme::sign(&private_key, message.as_bytes()).unwrap();
+
+        let proof_of_possession = SigScheme::sign(&new_private_key, message.as_bytes()).unwrap();
+
+        // the new key has to prove its possession
+        assert!(!controller.update_node_key(
+            String::from("0x0"),
+            new_key.clone(),
+            signature.clone(),
+            signature.clone(),
+        ));
+
+        assert!(controller.update_node_key(
+            String::from("0x0"),
+            new_key.clone(),
+            signature.clone(),
+            proof_of_possession.clone(),
+        ));
+
+        assert_eq!(
+            controller.get_node(String::from("0x0")).id_public_key,
+            new_key
+        );
+        assert_eq!(controller.get_group(GroupIndex(1)).epoch, epoch.next());
+
+        let dkg_task = controller.emit_dkg_task(0).pop().unwrap();
+
+        assert_eq!(dkg_task.epoch, epoch.next());
+
+        // the update was made for the previous key
+        assert!(!controller.update_node_key(
+            String::from("0x0"),
+            new_key,
+            signature,
+            proof_of_possession,
+        ));
+    }
+
+    #[test]
+    fn challenges_are_emitted_to_the_committer() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        // 5 members with a threshold of 3, so that 2 partial signatures are not aggregated
+        register_nodes(&mut controller, 5);
+
+        let (private_key, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        // the member whose partial signature is the last one to be aggregated
+        let (forger, (forger_index, forger_key)) = member_keys
+            .iter()
+            .max_by_key(|(_, (index, _))| *index)
+            .map(|(id_address, keys)| (id_address.clone(), *keys))
+            .unwrap();
+
+        let fulfill_next = |controller: &mut Controller, message: &str, forged: bool| {
+            assert!(request_randomness(controller, message));
+
+            let task = controller.pending_signature_tasks()[0].clone();
+
+            let committer = controller.get_group(GroupIndex(1)).committers[0].clone();
+
+            let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+            let mut partial_signatures = sign_partially(&member_keys, &task.message());
+
+            if forged {
+                let forged_partial = Eval {
+                    value: SigScheme::sign(&forger_key, b"another message").unwrap(),
+                    index: forger_index,
+                };
+
+                partial_signatures
+                    .insert(forger.clone(), bincode::serialize(&forged_partial).unwrap());
+            }
+
+            assert!(controller.fulfill(
+                committer.clone(),
+                task.index,
+                signature,
+                partial_signatures
+            ));
+
+            (task.index, committer)
+        };
+
+        let (signature_index, committer) = fulfill_next(&mut controller, "honest", false);
+
+        assert!(!controller.challenge_reward(String::from("0xchallenger"), signature_index));
+
+        let event = controller.emit_challenge_event(0)[0].clone();
+
+        assert_eq!(event.committer, committer);
+        assert_eq!(event.invalid_partial_signer, None);
+        assert_eq!(
+            controller.get_node(committer.clone()).staking,
+            NODE_STAKING_AMOUNT
+        );
+
+        // the committer re-verifies what was challenged from the event alone
+        assert!(event
+            .partial_signatures
+            .iter()
+            .all(|(id_address, partial)| {
+                verify_partial_signature(
+                    &event.signature_task.eligible_members[id_address],
+                    &event.signature_task.message(),
+                    partial,
+                )
+            }));
+
+        // the forged partial signature is left out of the aggregate, so the fulfillment
+        // goes through and only a challenge catches it
+        let (signature_index, committer) = fulfill_next(&mut controller, "tampered", true);
+
+        assert!(controller.challenge_reward(String::from("0xchallenger"), signature_index));
+
+        let event = controller.emit_challenge_event(1)[0];
+
+        assert_eq!(event.invalid_partial_signer, Some(forger));
+        assert_eq!(
+            controller.get_node(committer.clone()).staking,
+            NODE_STAKING_AMOUNT - COMMITTER_PENALTY_PER_SIGNATURE
+        );
+        assert_eq!(
+            controller.get_reward_statement(&committer).unwrap().slashed,
+            COMMITTER_PENALTY_PER_SIGNATURE
+        );
+    }
+
+    #[test]
+    fn simulations_leave_the_state_untouched() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 3);
+
+        let dkg_task = (*controller.emit_dkg_task(0).last().unwrap()).clone();
+
+        let state_hash = controller.state_hash();
+
+        assert!(controller.simulate_commit_dkg(
+            String::from("0x0"),
+            dkg_task.group_index,
+            dkg_task.epoch,
+            vec![1],
+            vec![],
+            vec![],
+        ));
+        assert!(!controller.simulate_commit_dkg(
+            String::from("0x0"),
+            dkg_task.group_index,
+            dkg_task.epoch.next(),
+            vec![1],
+            vec![],
+            vec![],
+        ));
+        assert_eq!(controller.state_hash(), state_hash);
+
+        let (private_key, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        assert!(request_randomness(&mut controller, "simulated"));
+
+        let task = controller.pending_signature_tasks()[0].clone();
+
+        let committer = controller.get_group(GroupIndex(1)).committers[0].clone();
+
+        let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+        let partial_signatures = sign_partially(&member_keys, &task.message());
+
+        let state_hash = controller.state_hash();
+
+        assert!(!controller.simulate_fulfill(
+            committer.clone(),
+            task.index,
+            signature.clone(),
+            HashMap::new()
+        ));
+        assert!(controller.simulate_fulfill(committer, task.index, signature, partial_signatures));
+        assert_eq!(controller.state_hash(), state_hash);
+        assert_eq!(controller.pending_signature_tasks().len(), 1);
+    }
+
+    #[test]
+    fn nodes_without_the_controller_scheme_cannot_register() {
+        let mut controller = Controller::new(String::from(OWNER), 0x8762_4875_6548_6346);
+
+        fund_stake(&mut controller, "0x1");
+
+        let capabilities = Capabilities {
+            schemes: vec![SchemeId::BLS12377G1].into_iter().collect(),
+            ..Capabilities::default()
+        };
+
+        assert!(!controller.node_register(
+            String::from("0x1"),
+            vec![],
+            String::from(""),
+            String::from("0x1"),
+            capabilities,
+        ));
+        assert!(!controller.nodes.contains_key("0x1"));
+
+        assert!(register_node(&mut controller, "0x1"));
+    }
 
     #[test]
     fn test_mut() {
@@ -0,0 +1,305 @@
+use crate::types::{Epoch, GroupIndex};
+use serde::Serialize;
+use std::collections::HashMap;
+
+pub const DEFAULT_PHASE_DURATION: usize = 10;
+
+/// Mock of the per-group DKG board. It mirrors `DKG.sol`, except that it is
+/// initialized by the controller with the group members instead of going
+/// through allowlisting and registration.
+#[derive(Clone)]
+pub struct Coordinator {
+    pub group_index: GroupIndex,
+    pub epoch: Epoch,
+    pub threshold: usize,
+    pub phase_duration: usize,
+    pub start_block: usize,
+    pub block_height: usize,
+    participants: Vec<String>,
+    keys: HashMap<String, Vec<u8>>,
+    shares: HashMap<String, Vec<u8>>,
+    responses: HashMap<String, Vec<u8>>,
+    justifications: HashMap<String, Vec<u8>>,
+}
+
+/// Everything published during a DKG ceremony. Each list is ordered like the
+/// participants, with an empty entry for participants who did not publish.
+#[derive(Clone, Debug, PartialEq, Hash, Serialize)]
+pub struct DKGTranscript {
+    pub group_index: GroupIndex,
+    pub epoch: Epoch,
+    pub threshold: usize,
+    pub participants: Vec<String>,
+    pub bls_keys: Vec<Vec<u8>>,
+    pub shares: Vec<Vec<u8>>,
+    pub responses: Vec<Vec<u8>>,
+    pub justifications: Vec<Vec<u8>>,
+}
+
+/// Who has published in each phase, in participant order, so that nodes can see
+/// who is lagging without decoding the board.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhaseProgress {
+    pub participants: Vec<String>,
+    pub shares: Vec<bool>,
+    pub responses: Vec<bool>,
+    pub justifications: Vec<bool>,
+}
+
+impl PhaseProgress {
+    pub fn shares_count(&self) -> usize {
+        self.shares.iter().filter(|&&published| published).count()
+    }
+
+    pub fn responses_count(&self) -> usize {
+        self.responses
+            .iter()
+            .filter(|&&published| published)
+            .count()
+    }
+
+    pub fn justifications_count(&self) -> usize {
+        self.justifications
+            .iter()
+            .filter(|&&published| published)
+            .count()
+    }
+}
+
+impl Coordinator {
+    pub fn new(
+        group_index: GroupIndex,
+        epoch: Epoch,
+        threshold: usize,
+        phase_duration: usize,
+    ) -> Self {
+        Coordinator {
+            group_index,
+            epoch,
+            threshold,
+            phase_duration,
+            start_block: 0,
+            block_height: 0,
+            participants: vec![],
+            keys: HashMap::new(),
+            shares: HashMap::new(),
+            responses: HashMap::new(),
+            justifications: HashMap::new(),
+        }
+    }
+}
+
+pub trait CoordinatorTransactions {
+    /// Starts the ceremony at `block_height` with the participants in DKG index order.
+    fn initialize(&mut self, block_height: usize, participants: Vec<(String, Vec<u8>)>);
+
+    /// Publishes the data of the current phase. Fails if the sender is not a participant,
+    /// has already published for this phase or if the DKG has ended.
+    fn publish(&mut self, id_address: String, value: Vec<u8>) -> bool;
+}
+
+pub trait CoordinatorViews {
+    fn get_shares(&self) -> Vec<Vec<u8>>;
+
+    fn get_responses(&self) -> Vec<Vec<u8>>;
+
+    fn get_justifications(&self) -> Vec<Vec<u8>>;
+
+    fn get_participants(&self) -> Vec<String>;
+
+    fn get_bls_keys(&self) -> (usize, Vec<Vec<u8>>);
+
+    /// Returns the current phase, from 1 to 3, or None if the DKG has ended.
+    fn in_phase(&self) -> Option<usize>;
+
+    /// Whether the participant already published their data for the current phase,
+    /// which a restarted participant checks instead of publishing again.
+    fn has_published(&self, id_address: &str) -> bool;
+
+    fn get_transcript(&self) -> DKGTranscript;
+
+    fn get_phase_progress(&self) -> PhaseProgress;
+}
+
+impl CoordinatorTransactions for Coordinator {
+    fn initialize(&mut self, block_height: usize, participants: Vec<(String, Vec<u8>)>) {
+        self.start_block = block_height;
+
+        self.block_height = block_height;
+
+        for (id_address, key) in participants {
+            self.participants.push(id_address.clone());
+
+            self.keys.insert(id_address, key);
+        }
+    }
+
+    fn publish(&mut self, id_address: String, value: Vec<u8>) -> bool {
+        if !self.keys.contains_key(&id_address) {
+            return false;
+        }
+
+        if self.has_published(&id_address) {
+            return false;
+        }
+
+        let board = match self.in_phase() {
+            Some(1) => &mut self.shares,
+            Some(2) => &mut self.responses,
+            Some(3) => &mut self.justifications,
+            _ => return false,
+        };
+
+        board.insert(id_address, value);
+
+        true
+    }
+}
+
+impl Coordinator {
+    fn in_participant_order(&self, board: &HashMap<String, Vec<u8>>) -> Vec<Vec<u8>> {
+        self.participants
+            .iter()
+            .map(|id_address| board.get(id_address).cloned().unwrap_or_default())
+            .collect::<Vec<_>>()
+    }
+
+    fn published_in_participant_order(&self, board: &HashMap<String, Vec<u8>>) -> Vec<bool> {
+        self.participants
+            .iter()
+            .map(|id_address| board.contains_key(id_address))
+            .collect::<Vec<_>>()
+    }
+}
+
+impl CoordinatorViews for Coordinator {
+    fn get_shares(&self) -> Vec<Vec<u8>> {
+        self.in_participant_order(&self.shares)
+    }
+
+    fn get_responses(&self) -> Vec<Vec<u8>> {
+        self.in_participant_order(&self.responses)
+    }
+
+    fn get_justifications(&self) -> Vec<Vec<u8>> {
+        self.in_participant_order(&self.justifications)
+    }
+
+    fn get_participants(&self) -> Vec<String> {
+        self.participants.clone()
+    }
+
+    fn get_bls_keys(&self) -> (usize, Vec<Vec<u8>>) {
+        (self.threshold, self.in_participant_order(&self.keys))
+    }
+
+    fn in_phase(&self) -> Option<usize> {
+        let blocks_since_start = self.block_height - self.start_block;
+
+        if blocks_since_start <= self.phase_duration {
+            return Some(1);
+        }
+
+        if blocks_since_start <= 2 * self.phase_duration {
+            return Some(2);
+        }
+
+        if blocks_since_start <= 3 * self.phase_duration {
+            return Some(3);
+        }
+
+        None
+    }
+
+    fn has_published(&self, id_address: &str) -> bool {
+        match self.in_phase() {
+            Some(1) => self.shares.contains_key(id_address),
+            Some(2) => self.responses.contains_key(id_address),
+            Some(3) => self.justifications.contains_key(id_address),
+            _ => false,
+        }
+    }
+
+    fn get_transcript(&self) -> DKGTranscript {
+        DKGTranscript {
+            group_index: self.group_index,
+            epoch: self.epoch,
+            threshold: self.threshold,
+            participants: self.get_participants(),
+            bls_keys: self.get_bls_keys().1,
+            shares: self.get_shares(),
+            responses: self.get_responses(),
+            justifications: self.get_justifications(),
+        }
+    }
+
+    fn get_phase_progress(&self) -> PhaseProgress {
+        PhaseProgress {
+            participants: self.get_participants(),
+            shares: self.published_in_participant_order(&self.shares),
+            responses: self.published_in_participant_order(&self.responses),
+            justifications: self.published_in_participant_order(&self.justifications),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn setup_coordinator() -> Coordinator {
+        let mut coordinator = Coordinator::new(GroupIndex(1), Epoch(1), 3, DEFAULT_PHASE_DURATION);
+
+        let participants = (0..3)
+            .map(|i| (format!("0x{}", i), vec![i as u8]))
+            .collect::<Vec<_>>();
+
+        coordinator.initialize(5, participants);
+
+        coordinator
+    }
+
+    #[test]
+    fn publish_follows_phases() {
+        let mut coordinator = setup_coordinator();
+
+        assert_eq!(coordinator.in_phase(), Some(1));
+        assert!(!coordinator.has_published("0x0"));
+        assert!(coordinator.publish(String::from("0x0"), vec![1]));
+        assert!(coordinator.has_published("0x0"));
+        assert!(!coordinator.publish(String::from("0x0"), vec![1]));
+        assert!(!coordinator.publish(String::from("0x9"), vec![1]));
+
+        coordinator.block_height += DEFAULT_PHASE_DURATION + 1;
+
+        assert_eq!(coordinator.in_phase(), Some(2));
+        assert!(!coordinator.has_published("0x0"));
+        assert!(coordinator.publish(String::from("0x0"), vec![2]));
+
+        coordinator.block_height += DEFAULT_PHASE_DURATION;
+
+        assert_eq!(coordinator.in_phase(), Some(3));
+        assert!(coordinator.publish(String::from("0x2"), vec![3]));
+
+        coordinator.block_height += DEFAULT_PHASE_DURATION;
+
+        assert_eq!(coordinator.in_phase(), None);
+        assert!(!coordinator.publish(String::from("0x1"), vec![3]));
+
+        let transcript = coordinator.get_transcript();
+
+        assert_eq!(transcript.participants, vec!["0x0", "0x1", "0x2"]);
+        assert_eq!(transcript.bls_keys, vec![vec![0], vec![1], vec![2]]);
+        assert_eq!(transcript.shares, vec![vec![1], vec![], vec![]]);
+        assert_eq!(transcript.responses, vec![vec![2], vec![], vec![]]);
+        assert_eq!(transcript.justifications, vec![vec![], vec![], vec![3]]);
+
+        let progress = coordinator.get_phase_progress();
+
+        assert_eq!(progress.shares, vec![true, false, false]);
+        assert_eq!(progress.responses, vec![true, false, false]);
+        assert_eq!(progress.justifications, vec![false, false, true]);
+        assert_eq!(progress.shares_count(), 1);
+        assert_eq!(progress.justifications_count(), 1);
+    }
+}
@@ -0,0 +1,452 @@
+use crate::capabilities::Capabilities;
+use crate::contract::{
+    partial_signature_length, request_fee, signature_length, InvariantViolation, MockHelper, Views,
+    CONTROLLER_ADDRESS, NODE_STAKING_AMOUNT,
+};
+use crate::recorder::{Recorder, Transaction, TransactionLog};
+use crate::types::{Epoch, GroupIndex, TaskIndex};
+use paired::bls12_381::G1;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+use threshold_bls::poly::{Eval, Poly};
+use threshold_bls::schemes::bls12_381::G1Scheme as SigScheme;
+use threshold_bls::sig::{Scheme, SignatureScheme};
+
+const OWNER: &str = "0xadmin";
+
+const REQUESTER: &str = "0xrequester";
+
+type PrivateKey = <SigScheme as Scheme>::Private;
+
+// the group key and the shares of the members along with their index
+type GroupKeys = (PrivateKey, HashMap<String, (u32, PrivateKey)>);
+
+#[derive(Debug, Error, PartialEq)]
+pub enum FuzzError {
+    #[error("seed {seed} step {step}: {violation}")]
+    InvariantViolated {
+        seed: u64,
+        step: usize,
+        violation: InvariantViolation,
+    },
+    #[error("seed {seed} step {step}: {minted} tokens were minted but {total_supply} exist")]
+    SupplyChanged {
+        seed: u64,
+        step: usize,
+        minted: usize,
+        total_supply: usize,
+    },
+}
+
+/// Applies seeded random sequences of transactions to a recorded controller and checks
+/// its invariants after each one. Most transactions carry arbitrary bytes, the rest are
+/// well-formed so that groups get formed and tasks signed, reaching the deeper paths of
+/// commit_dkg and fulfill. A failing sequence can be replayed from `log`.
+pub struct Fuzzer {
+    seed: u64,
+    rng: StdRng,
+    recorder: Recorder,
+    // the keys committed by the members of a group epoch, to sign its tasks
+    keys: HashMap<(GroupIndex, Epoch), GroupKeys>,
+    registered: usize,
+    minted: usize,
+    steps: usize,
+}
+
+impl Fuzzer {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let initial_entropy = rng.gen();
+
+        Fuzzer {
+            seed,
+            rng,
+            recorder: Recorder::new(String::from(OWNER), initial_entropy),
+            keys: HashMap::new(),
+            registered: 0,
+            minted: 0,
+            steps: 0,
+        }
+    }
+
+    pub fn log(&self) -> &TransactionLog {
+        self.recorder.log()
+    }
+
+    /// Applies `steps` more transactions, stopping at the first broken invariant.
+    pub fn run(&mut self, steps: usize) -> Result<(), FuzzError> {
+        for _ in 0..steps {
+            self.step()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn step(&mut self) -> Result<(), FuzzError> {
+        for transaction in self.next_transactions() {
+            if let Transaction::Mint { amount, .. } = transaction {
+                self.minted += amount;
+            }
+
+            self.recorder.apply(transaction);
+        }
+
+        self.steps += 1;
+
+        let controller = self.recorder.controller();
+
+        controller
+            .check_invariants()
+            .map_err(|violation| FuzzError::InvariantViolated {
+                seed: self.seed,
+                step: self.steps,
+                violation,
+            })?;
+
+        let total_supply = controller.token.total_supply();
+
+        if total_supply != self.minted {
+            return Err(FuzzError::SupplyChanged {
+                seed: self.seed,
+                step: self.steps,
+                minted: self.minted,
+                total_supply,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn next_transactions(&mut self) -> Vec<Transaction> {
+        match self.rng.gen_range(0, 15) {
+            0 | 1 => self.register_node(),
+            2 => self.commit_latest_dkg_task(),
+            3 => vec![self.arbitrary_commit()],
+            4 => self.request(),
+            5 | 6 => self.fulfill_pending_task(),
+            7 => vec![self.arbitrary_fulfill()],
+            8 => vec![Transaction::ChallengeReward {
+                id_address: self.address(),
+                signature_index: self.signature_index(),
+            }],
+            9 => vec![Transaction::DisbandGroup {
+                id_address: if self.rng.gen() {
+                    String::from(OWNER)
+                } else {
+                    self.address()
+                },
+                group_index: self.group_index(),
+            }],
+            10 => vec![Transaction::CheckDkgState {
+                id_address: self.address(),
+                group_index: self.group_index(),
+            }],
+            11 => vec![Transaction::Mine {
+                block_number: self.rng.gen_range(0, 50),
+            }],
+            12 => vec![Transaction::Claim {
+                id_address: self.address(),
+            }],
+            13 => vec![Transaction::NodeActivate {
+                id_address: self.address(),
+            }],
+            _ => vec![Transaction::UpdateNodeKey {
+                id_address: self.address(),
+                id_public_key: self.bytes(),
+                signature: self.bytes(),
+                proof_of_possession: self.bytes(),
+            }],
+        }
+    }
+
+    /// Registers the next node, funded most of the time.
+    fn register_node(&mut self) -> Vec<Transaction> {
+        let id_address = format!("0x{}", self.registered);
+
+        self.registered += 1;
+
+        let mut transactions = vec![];
+
+        if self.rng.gen_range(0, 10) > 0 {
+            transactions.push(Transaction::Mint {
+                to: id_address.clone(),
+                amount: NODE_STAKING_AMOUNT,
+            });
+
+            transactions.push(Transaction::Approve {
+                owner: id_address.clone(),
+                spender: String::from(CONTROLLER_ADDRESS),
+                amount: NODE_STAKING_AMOUNT,
+            });
+        }
+
+        let (_, id_public_key) = SigScheme::keypair(&mut self.rng);
+
+        transactions.push(Transaction::NodeRegister {
+            id_address: id_address.clone(),
+            id_public_key: bincode::serialize(&id_public_key).unwrap(),
+            endpoint: String::from(""),
+            reward_address: id_address,
+            capabilities: Capabilities::default(),
+        });
+
+        transactions
+    }
+
+    /// Every member of the latest DKG task of a group, but the occasional absent one,
+    /// commits the same fresh group key, sometimes disqualifying a member.
+    fn commit_latest_dkg_task(&mut self) -> Vec<Transaction> {
+        let mut latest_dkg_tasks = BTreeMap::new();
+
+        for dkg_task in self.recorder.controller().emit_dkg_task(0) {
+            latest_dkg_tasks.insert(dkg_task.group_index, dkg_task.clone());
+        }
+
+        if latest_dkg_tasks.is_empty() {
+            return vec![];
+        }
+
+        let dkg_task = latest_dkg_tasks
+            .values()
+            .nth(self.rng.gen_range(0, latest_dkg_tasks.len()))
+            .cloned()
+            .unwrap();
+
+        let private_poly = Poly::<PrivateKey>::new_from(dkg_task.threshold - 1, &mut self.rng);
+
+        let public_poly = private_poly.commit::<G1>();
+
+        let (private_key, public_key) = (*private_poly.public_key(), *public_poly.public_key());
+
+        let mut members = dkg_task.members.iter().collect::<Vec<_>>();
+
+        members.sort_by_key(|(_, member_index)| **member_index);
+
+        let disqualified_nodes = match self.rng.gen_range(0, 4) {
+            0 => vec![members[self.rng.gen_range(0, members.len())].0.clone()],
+            _ => vec![],
+        };
+
+        let mut member_keys = HashMap::new();
+
+        let mut transactions = vec![];
+
+        for (id_address, member_index) in members {
+            if self.rng.gen_range(0, 8) == 0 {
+                continue;
+            }
+
+            let member_private_key = private_poly.eval(member_index.dkg_index()).value;
+
+            let member_public_key = public_poly.eval(member_index.dkg_index()).value;
+
+            transactions.push(Transaction::CommitDkg {
+                id_address: id_address.clone(),
+                group_index: dkg_task.group_index,
+                group_epoch: dkg_task.epoch,
+                public_key: bincode::serialize(&public_key).unwrap(),
+                partial_public_key: bincode::serialize(&member_public_key).unwrap(),
+                disqualified_nodes: disqualified_nodes.clone(),
+            });
+
+            member_keys.insert(
+                id_address.clone(),
+                (member_index.dkg_index(), member_private_key),
+            );
+        }
+
+        self.keys.insert(
+            (dkg_task.group_index, dkg_task.epoch),
+            (private_key, member_keys),
+        );
+
+        transactions
+    }
+
+    fn arbitrary_commit(&mut self) -> Transaction {
+        Transaction::CommitDkg {
+            id_address: self.address(),
+            group_index: self.group_index(),
+            group_epoch: Epoch(self.rng.gen_range(0, 5)),
+            public_key: self.bytes(),
+            partial_public_key: self.bytes(),
+            disqualified_nodes: (0..self.rng.gen_range(0, 3))
+                .map(|_| self.address())
+                .collect(),
+        }
+    }
+
+    /// Requests up to 3 words, funded with the fee most of the time.
+    fn request(&mut self) -> Vec<Transaction> {
+        let num_words = self.rng.gen_range(1, 4);
+
+        let mut transactions = vec![];
+
+        if self.rng.gen_range(0, 10) > 0 {
+            transactions.push(Transaction::Mint {
+                to: String::from(REQUESTER),
+                amount: request_fee(num_words),
+            });
+
+            transactions.push(Transaction::Approve {
+                owner: String::from(REQUESTER),
+                spender: String::from(CONTROLLER_ADDRESS),
+                amount: request_fee(num_words),
+            });
+        }
+
+        transactions.push(Transaction::Request {
+            requester: String::from(REQUESTER),
+            seed: self.bytes(),
+            num_words,
+        });
+
+        transactions
+    }
+
+    /// Signs a pending task with the keys committed for its group, leaving out or
+    /// corrupting a partial signature now and then.
+    fn fulfill_pending_task(&mut self) -> Vec<Transaction> {
+        let controller = self.recorder.controller();
+
+        let pending_signature_tasks = controller.pending_signature_tasks();
+
+        if pending_signature_tasks.is_empty() {
+            return vec![];
+        }
+
+        let signature_task =
+            pending_signature_tasks[self.rng.gen_range(0, pending_signature_tasks.len())].clone();
+
+        let group = controller.get_group(signature_task.group_index);
+
+        let (private_key, member_keys) = match self.keys.get(&(group.index, group.epoch)) {
+            Some(keys) => keys,
+            None => return vec![],
+        };
+
+        let id_address = match group.committers.first() {
+            Some(committer) => committer.clone(),
+            None => return vec![],
+        };
+
+        let message = signature_task.message();
+
+        let signature = SigScheme::sign(private_key, &message).unwrap();
+
+        let mut partial_signatures = member_keys
+            .iter()
+            .map(|(id_address, (index, private_key))| {
+                let partial = Eval {
+                    value: SigScheme::sign(private_key, &message).unwrap(),
+                    index: *index,
+                };
+
+                (id_address.clone(), bincode::serialize(&partial).unwrap())
+            })
+            .collect::<HashMap<_, _>>();
+
+        match self.rng.gen_range(0, 6) {
+            0 => {
+                let id_address = partial_signatures.keys().next().cloned();
+
+                if let Some(id_address) = id_address {
+                    partial_signatures.remove(&id_address);
+                }
+            }
+            1 => {
+                // flips a bit of the signature, right after its length prefix
+                if let Some(partial_signature) = partial_signatures.values_mut().next() {
+                    partial_signature[8] ^= 1;
+                }
+            }
+            _ => {}
+        }
+
+        vec![Transaction::Fulfill {
+            id_address,
+            signature_index: signature_task.index,
+            signature,
+            partial_signatures,
+        }]
+    }
+
+    /// A fulfillment from anyone, with random signatures of the expected lengths or not.
+    fn arbitrary_fulfill(&mut self) -> Transaction {
+        let well_sized = self.rng.gen();
+
+        let signature = if well_sized {
+            self.bytes_of(signature_length())
+        } else {
+            self.bytes()
+        };
+
+        let partial_signatures = (0..self.rng.gen_range(0, 6))
+            .map(|_| {
+                let partial_signature = if well_sized {
+                    self.bytes_of(partial_signature_length())
+                } else {
+                    self.bytes()
+                };
+
+                (self.address(), partial_signature)
+            })
+            .collect();
+
+        Transaction::Fulfill {
+            id_address: self.address(),
+            signature_index: self.signature_index(),
+            signature,
+            partial_signatures,
+        }
+    }
+
+    /// A registered node most of the time, or an unknown address.
+    fn address(&mut self) -> String {
+        format!("0x{}", self.rng.gen_range(0, self.registered + 2))
+    }
+
+    fn group_index(&mut self) -> GroupIndex {
+        GroupIndex(self.rng.gen_range(0, 6))
+    }
+
+    fn signature_index(&mut self) -> TaskIndex {
+        let signature_count = self.recorder.controller().signature_count;
+
+        TaskIndex(self.rng.gen_range(0, signature_count + 2))
+    }
+
+    fn bytes(&mut self) -> Vec<u8> {
+        let len = self.rng.gen_range(0, 64);
+
+        self.bytes_of(len)
+    }
+
+    fn bytes_of(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.rng.gen()).collect()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[test]
+    fn random_transaction_sequences_preserve_invariants() {
+        for seed in 0..50 {
+            let mut fuzzer = Fuzzer::new(seed);
+
+            let result = catch_unwind(AssertUnwindSafe(|| fuzzer.run(200)));
+
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => panic!("{}", err),
+                Err(_) => panic!("seed {} panicked", seed),
+            }
+        }
+    }
+}
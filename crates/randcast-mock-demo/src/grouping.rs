@@ -0,0 +1,87 @@
+use crate::contract::{Group, Node};
+use crate::mixing::MixingFunction;
+use crate::rebalance::{plan_rebalance, Move};
+use crate::types::GroupIndex;
+use std::collections::BTreeMap;
+
+/// Decides which group a node joins and how members move between groups to fill the
+/// undersized ones. Both must only depend on their arguments, so that replaying the
+/// same transactions forms the same groups.
+pub trait GroupingStrategy {
+    /// Picks the group a node joins among `candidates`, the groups with room left in
+    /// index order. None if there is no candidate.
+    fn choose_group(
+        &self,
+        candidates: &[&Group],
+        nodes: &BTreeMap<String, Node>,
+    ) -> Option<GroupIndex>;
+
+    /// Plans the moves bringing the undersized groups among `members` up to `minimum`.
+    fn plan_rebalance(
+        &self,
+        members: &BTreeMap<GroupIndex, Vec<String>>,
+        minimum: &dyn Fn(GroupIndex) -> usize,
+        seed: u64,
+        mixing: MixingFunction,
+    ) -> Vec<Move> {
+        plan_rebalance(members, minimum, seed, mixing)
+    }
+}
+
+/// Fills the smallest group first, ties go to the lowest index.
+pub struct SmallestGroup;
+
+impl GroupingStrategy for SmallestGroup {
+    fn choose_group(
+        &self,
+        candidates: &[&Group],
+        _nodes: &BTreeMap<String, Node>,
+    ) -> Option<GroupIndex> {
+        candidates
+            .iter()
+            .min_by_key(|group| (group.size, group.index))
+            .map(|group| group.index)
+    }
+}
+
+/// Fills the group with the least stake behind it first, so that slashed members get
+/// made up for by new ones. Ties go to the smallest group, then the lowest index.
+pub struct StakeWeighted;
+
+impl GroupingStrategy for StakeWeighted {
+    fn choose_group(
+        &self,
+        candidates: &[&Group],
+        nodes: &BTreeMap<String, Node>,
+    ) -> Option<GroupIndex> {
+        candidates
+            .iter()
+            .min_by_key(|group| {
+                let stake: usize = group
+                    .members
+                    .keys()
+                    .filter_map(|id_address| nodes.get(id_address))
+                    .map(|node| node.staking)
+                    .sum();
+
+                (stake, group.size, group.index)
+            })
+            .map(|group| group.index)
+    }
+}
+
+/// The built-in strategies, selected through the controller's config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Grouping {
+    SmallestGroup,
+    StakeWeighted,
+}
+
+impl Grouping {
+    pub fn strategy(self) -> &'static dyn GroupingStrategy {
+        match self {
+            Grouping::SmallestGroup => &SmallestGroup,
+            Grouping::StakeWeighted => &StakeWeighted,
+        }
+    }
+}
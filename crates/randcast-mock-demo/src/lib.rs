@@ -1,5 +1,27 @@
 pub mod actions;
 
+pub mod capabilities;
+
 pub mod contract;
 
+pub mod coordinator;
+
+pub mod fuzz;
+
+pub mod grouping;
+
+pub mod mixing;
+
+pub mod rebalance;
+
+pub mod recorder;
+
+pub mod scenario;
+
 pub mod test_helpers;
+
+pub mod token;
+
+pub mod types;
+
+pub mod vrf;
@@ -5,13 +5,15 @@ use dkg_core::primitives::{
 };
 use dkg_core::{DKGPhase, Phase2Result};
 use rand::rngs::ThreadRng;
+use randcast_mock_demo::capabilities::Capabilities;
 use randcast_mock_demo::contract::*;
 use randcast_mock_demo::test_helpers::InMemoryBoard;
+use randcast_mock_demo::types::TaskIndex;
 use std::collections::HashMap;
 use threshold_bls::{
     curve::bls12381::{self, PairingCurve as BLS12_381},
     group::Curve,
-    poly::Idx,
+    poly::{Eval, Idx},
     sig::{G1Scheme, Scheme, SignatureScheme, ThresholdScheme},
 };
 
@@ -24,7 +26,7 @@ async fn main() -> anyhow::Result<()> {
         initial_entropy
     );
 
-    let mut controller = Controller::new(initial_entropy);
+    let mut controller = Controller::new(String::from("0xadmin"), initial_entropy);
 
     let (t, n) = (3, 5);
 
@@ -37,17 +39,27 @@ async fn main() -> anyhow::Result<()> {
     println!("nodes are registering to controller...");
 
     phase0s.iter().enumerate().for_each(|(i, phase0)| {
+        let id_address = String::from("0x") + &i.to_string();
+
+        // mock: every node is funded with its stake
+        controller.token.mint(&id_address, NODE_STAKING_AMOUNT);
+
+        controller
+            .token
+            .approve(&id_address, CONTROLLER_ADDRESS, NODE_STAKING_AMOUNT);
+
         controller.node_register(
-            String::from("0x") + &i.to_string(),
+            id_address.clone(),
             bincode::serialize(&phase0.info.public_key).unwrap(),
             String::from(""),
-            String::from("0x") + &i.to_string(),
+            id_address,
+            Capabilities::default(),
         );
     });
 
     println!("DKG task is emitting...");
 
-    let dkg_task = controller.emit_dkg_task();
+    let dkg_task = *controller.emit_dkg_task(0).last().unwrap();
 
     let group_index = dkg_task.group_index;
 
@@ -56,6 +68,15 @@ async fn main() -> anyhow::Result<()> {
     // executes the DKG state machine and ensures that the keys are generated correctly
     let outputs = run_dkg::<bls12381::Curve, G1Scheme<BLS12_381>>(&mut board, phase0s, rng).await;
 
+    // the controller's view of the group must match the one the DKG ran with
+    for output in outputs.iter() {
+        check_dkg_output(
+            dkg_task,
+            output.qual.threshold,
+            output.qual.nodes.iter().map(Node::id),
+        )?;
+    }
+
     // get the public key (we have already checked that all outputs' pubkeys are the same)
     let public_poly = outputs[0].public.clone();
 
@@ -75,44 +96,69 @@ async fn main() -> anyhow::Result<()> {
         println!("{}-res: {}", i, res);
     });
 
-    let group = controller.get_group(1);
+    let group = controller.get_group(group_index);
 
-    println!("group state: {}", group.state);
+    println!("group state: {:?}", group.state);
 
     println!("group committers: {:?}", group.committers);
 
     // let msg = rand::random::<[u8; 32]>().to_vec();
 
-    let msg = String::from("ujehwsndfgljkhrlkg");
+    let seed = String::from("ujehwsndfgljkhrlkg");
+
+    println!("An user is requesting a randomness... seed: {}", seed);
+
+    let requester = String::from("0xuser");
 
-    println!("An user is requesting a randomness... msg seed: {}", msg);
+    controller.token.mint(&requester, request_fee(1));
 
-    let request_res = controller.request(msg.clone());
+    controller
+        .token
+        .approve(&requester, CONTROLLER_ADDRESS, request_fee(1));
+
+    let request_res = controller.request(requester, seed.into_bytes(), 1);
 
     println!("request_res: {}", request_res);
 
     println!("A signature task is emitting...");
 
-    let signature_task = controller.emit_signature_task();
+    let signature_task = *controller.emit_signature_task(TaskIndex(0)).last().unwrap();
 
     let signature_index = signature_task.index;
 
+    // the seed is signed along with the requester and the block height
+    let msg = signature_task.message();
+
     // generates a partial sig with each share from the dkg
     let partial_sigs = outputs
         .iter()
-        .map(|output| G1Scheme::<BLS12_381>::partial_sign(&output.share, &msg.as_bytes()).unwrap())
+        .map(|output| G1Scheme::<BLS12_381>::partial_sign(&output.share, &msg).unwrap())
         .collect::<Vec<_>>();
 
-    // committer verify the partial threshold signatures first
-    partial_sigs.iter().for_each(|partial_sig| {
-        G1Scheme::<BLS12_381>::partial_verify(&public_poly, &msg.as_bytes(), &partial_sig).unwrap();
-    });
+    // committer verify the partial threshold signatures first, all at once
+    let (partial_public_keys, partial_values): (Vec<_>, Vec<_>) = partial_sigs
+        .iter()
+        .map(|partial_sig| {
+            let partial: Eval<Vec<u8>> = bincode::deserialize(partial_sig).unwrap();
+
+            (public_poly.eval(partial.index).value, partial.value)
+        })
+        .unzip();
+
+    let partial_values = partial_values.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+    G1Scheme::<BLS12_381>::aggregation_verify_on_the_same_msg(
+        &partial_public_keys,
+        &msg,
+        &partial_values,
+    )
+    .unwrap();
 
     // then aggregates them
     let sig = G1Scheme::<BLS12_381>::aggregate(t, &partial_sigs).unwrap();
 
     // committer verify the threshold signature first
-    G1Scheme::<BLS12_381>::verify(&pubkey, &msg.as_bytes(), &sig).unwrap();
+    G1Scheme::<BLS12_381>::verify(&pubkey, &msg, &sig).unwrap();
 
     println!("Committers are committing result of the signature task...");
 
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+
+/// The hash the controller derives randomness, committer seeds and rebalancing picks
+/// with. Values are hashed in their bincode encoding, so the same inputs give the same
+/// outputs on every platform and Rust version, which std's DefaultHasher doesn't
+/// guarantee. Switching the function changes every derived value from then on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MixingFunction {
+    /// What an EVM contract would use.
+    #[default]
+    Keccak256,
+    Sha256,
+}
+
+impl MixingFunction {
+    pub fn digest(self, bytes: &[u8]) -> [u8; 32] {
+        let mut digest = [0u8; 32];
+
+        match self {
+            MixingFunction::Keccak256 => digest.copy_from_slice(&Keccak256::digest(bytes)),
+            MixingFunction::Sha256 => digest.copy_from_slice(&Sha256::digest(bytes)),
+        }
+
+        digest
+    }
+
+    /// Hashes the value into the first 8 bytes of its digest, read big endian.
+    pub fn mix<T: Serialize + ?Sized>(self, value: &T) -> u64 {
+        let bytes = bincode::serialize(value).expect("could not serialize");
+
+        let digest = self.digest(&bytes);
+
+        let mut word = [0u8; 8];
+
+        word.copy_from_slice(&digest[..8]);
+
+        u64::from_be_bytes(word)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn digests_match_the_reference_vectors() {
+        assert_eq!(
+            hex::encode(MixingFunction::Keccak256.digest(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::encode(MixingFunction::Sha256.digest(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        // the output is pinned, a change here breaks every recorded derivation
+        assert_eq!(MixingFunction::Keccak256.mix(&0u64), 0x011b4d03dd8c01f1);
+    }
+}
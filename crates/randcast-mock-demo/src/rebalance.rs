@@ -0,0 +1,304 @@
+use crate::mixing::MixingFunction;
+use crate::types::GroupIndex;
+use std::collections::BTreeMap;
+
+/// A member leaving one group for another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub id_address: String,
+    pub from_group_index: GroupIndex,
+    pub to_group_index: GroupIndex,
+}
+
+/// Plans the member movements needed to bring undersized groups up to their minimum
+/// size, given by `minimum` for each group index. `groups` maps each group index to the id
+/// addresses of its members. The function is pure: the same groups, seed and mixing
+/// function always yield the same plan, regardless of the order members are listed in.
+///
+/// The plan upholds the following invariants:
+/// - only non-empty groups below the minimum receive members, and only if they can be
+///   filled up to exactly the minimum; a group is never left half-filled
+/// - a group donates only while it stays at or above the minimum afterwards
+/// - a member moves at most once, and always out of the group it belongs to
+/// - the total number of members is preserved
+pub fn plan_rebalance(
+    groups: &BTreeMap<GroupIndex, Vec<String>>,
+    minimum: impl Fn(GroupIndex) -> usize,
+    seed: u64,
+    mixing: MixingFunction,
+) -> Vec<Move> {
+    let mut members = groups
+        .iter()
+        .map(|(&index, members)| {
+            let mut members = members.clone();
+            members.sort();
+            (index, members)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    // the closest to the minimum get filled first as they need the fewest moves
+    let mut recipients = members
+        .iter()
+        .filter(|(&index, members)| !members.is_empty() && members.len() < minimum(index))
+        .map(|(&index, members)| (index, members.len()))
+        .collect::<Vec<_>>();
+
+    recipients.sort_by(|(a_index, a_size), (b_index, b_size)| {
+        b_size.cmp(a_size).then(a_index.cmp(b_index))
+    });
+
+    let mut plan = vec![];
+
+    for (recipient_index, recipient_size) in recipients {
+        let needed = minimum(recipient_index) - recipient_size;
+
+        let spare: usize = members
+            .iter()
+            .filter(|(&index, _)| index != recipient_index)
+            .map(|(&index, members)| members.len().saturating_sub(minimum(index)))
+            .sum();
+
+        if spare < needed {
+            continue;
+        }
+
+        for _ in 0..needed {
+            // the largest group donates, ties go to the lowest index
+            let donor_index = *members
+                .iter()
+                .filter(|(&index, members)| {
+                    index != recipient_index && members.len() > minimum(index)
+                })
+                .max_by(|(a_index, a_members), (b_index, b_members)| {
+                    a_members
+                        .len()
+                        .cmp(&b_members.len())
+                        .then(b_index.cmp(a_index))
+                })
+                .map(|(index, _)| index)
+                .unwrap();
+
+            let donor = members.get_mut(&donor_index).unwrap();
+
+            let position = pick(mixing, seed, plan.len()) % donor.len();
+
+            let id_address = donor.remove(position);
+
+            members
+                .get_mut(&recipient_index)
+                .unwrap()
+                .push(id_address.clone());
+
+            plan.push(Move {
+                id_address,
+                from_group_index: donor_index,
+                to_group_index: recipient_index,
+            });
+        }
+    }
+
+    plan
+}
+
+fn pick(mixing: MixingFunction, seed: u64, step: usize) -> usize {
+    mixing.mix(&(seed, step as u64)) as usize
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::contract::DEFAULT_MINIMUM_THRESHOLD;
+
+    fn groups_of(sizes: &[usize]) -> BTreeMap<GroupIndex, Vec<String>> {
+        let mut id = 0;
+
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &size)| {
+                let members = (0..size)
+                    .map(|_| {
+                        id += 1;
+                        format!("0x{}", id)
+                    })
+                    .collect::<Vec<_>>();
+                (GroupIndex(i + 1), members)
+            })
+            .collect()
+    }
+
+    fn apply(
+        groups: &BTreeMap<GroupIndex, Vec<String>>,
+        plan: &[Move],
+    ) -> BTreeMap<GroupIndex, Vec<String>> {
+        let mut groups = groups.clone();
+
+        for m in plan {
+            let from = groups.get_mut(&m.from_group_index).unwrap();
+            let position = from.iter().position(|id| id == &m.id_address).unwrap();
+            from.remove(position);
+            groups
+                .get_mut(&m.to_group_index)
+                .unwrap()
+                .push(m.id_address.clone());
+        }
+
+        groups
+    }
+
+    fn check_invariants(sizes: &[usize], seed: u64) {
+        let groups = groups_of(sizes);
+
+        let plan = plan_rebalance(
+            &groups,
+            |_| DEFAULT_MINIMUM_THRESHOLD,
+            seed,
+            MixingFunction::default(),
+        );
+
+        assert_eq!(
+            plan,
+            plan_rebalance(
+                &groups,
+                |_| DEFAULT_MINIMUM_THRESHOLD,
+                seed,
+                MixingFunction::default()
+            )
+        );
+
+        let mut moved = plan.iter().map(|m| &m.id_address).collect::<Vec<_>>();
+        moved.sort();
+        moved.dedup();
+        assert_eq!(moved.len(), plan.len(), "{:?}: a member moved twice", sizes);
+
+        for m in &plan {
+            assert!(groups[&m.from_group_index].contains(&m.id_address));
+            assert_ne!(m.from_group_index, m.to_group_index);
+        }
+
+        let after = apply(&groups, &plan);
+
+        let total = |groups: &BTreeMap<GroupIndex, Vec<String>>| -> usize {
+            groups.values().map(|members| members.len()).sum()
+        };
+        assert_eq!(total(&groups), total(&after));
+
+        for (index, members) in &groups {
+            let size_after = after[index].len();
+            let received = plan.iter().any(|m| m.to_group_index == *index);
+            let donated = plan.iter().any(|m| m.from_group_index == *index);
+
+            if received {
+                assert!(!members.is_empty() && members.len() < DEFAULT_MINIMUM_THRESHOLD);
+                assert_eq!(size_after, DEFAULT_MINIMUM_THRESHOLD, "{:?}", sizes);
+                assert!(!donated);
+            }
+
+            if donated {
+                assert!(size_after >= DEFAULT_MINIMUM_THRESHOLD, "{:?}", sizes);
+            }
+        }
+    }
+
+    #[test]
+    fn plan_rebalance_invariants_hold_exhaustively() {
+        for a in 0..=10 {
+            for b in 0..=10 {
+                for c in 0..=10 {
+                    for d in 0..=4 {
+                        check_invariants(&[a, b, c, d], 0x8762_4875_6548_6346);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn plan_rebalance_invariants_hold_for_any_seed() {
+        for seed in 0..200 {
+            check_invariants(&[7, 1, 2, 4], seed);
+            check_invariants(&[1, 1, 1, 10], seed);
+        }
+    }
+
+    #[test]
+    fn plan_rebalance_fills_new_group_from_largest() {
+        let groups = groups_of(&[5, 4, 1]);
+
+        let plan = plan_rebalance(
+            &groups,
+            |_| DEFAULT_MINIMUM_THRESHOLD,
+            42,
+            MixingFunction::default(),
+        );
+
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[0].from_group_index, GroupIndex(1));
+        // group 1 and 2 both have 4 members left, the lower index donates
+        assert_eq!(plan[1].from_group_index, GroupIndex(1));
+        assert!(plan.iter().all(|m| m.to_group_index == GroupIndex(3)));
+    }
+
+    #[test]
+    fn plan_rebalance_leaves_unfillable_groups_alone() {
+        // only one member is spare, which can't fill a group of 1
+        let plan = plan_rebalance(
+            &groups_of(&[4, 1]),
+            |_| DEFAULT_MINIMUM_THRESHOLD,
+            42,
+            MixingFunction::default(),
+        );
+        assert!(plan.is_empty());
+
+        // but it is enough to fill a group of 2
+        let plan = plan_rebalance(
+            &groups_of(&[4, 1, 2]),
+            |_| DEFAULT_MINIMUM_THRESHOLD,
+            42,
+            MixingFunction::default(),
+        );
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].to_group_index, GroupIndex(3));
+    }
+
+    #[test]
+    fn plan_rebalance_ignores_member_order() {
+        let groups = groups_of(&[6, 1]);
+
+        let mut reversed = groups.clone();
+        reversed.values_mut().for_each(|members| members.reverse());
+
+        assert_eq!(
+            plan_rebalance(
+                &groups,
+                |_| DEFAULT_MINIMUM_THRESHOLD,
+                7,
+                MixingFunction::default()
+            ),
+            plan_rebalance(
+                &reversed,
+                |_| DEFAULT_MINIMUM_THRESHOLD,
+                7,
+                MixingFunction::default()
+            )
+        );
+    }
+
+    #[test]
+    fn plan_rebalance_depends_on_seed() {
+        let groups = groups_of(&[10, 1]);
+
+        let plans = (0..20)
+            .map(|seed| {
+                plan_rebalance(
+                    &groups,
+                    |_| DEFAULT_MINIMUM_THRESHOLD,
+                    seed,
+                    MixingFunction::default(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert!(plans.iter().any(|plan| plan != &plans[0]));
+    }
+}
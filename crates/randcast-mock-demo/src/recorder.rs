@@ -0,0 +1,588 @@
+use crate::capabilities::Capabilities;
+use crate::contract::{Controller, GroupConfig, MockHelper, Transactions, Views};
+use crate::types::{bytes_or_string, Epoch, GroupIndex, TaskIndex};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// A transaction applied to the controller, with its arguments.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Transaction {
+    NodeRegister {
+        id_address: String,
+        id_public_key: Vec<u8>,
+        endpoint: String,
+        reward_address: String,
+        // logs recorded before capabilities were advertised replay with the defaults
+        #[serde(default)]
+        capabilities: Capabilities,
+    },
+    UpdateNodeKey {
+        id_address: String,
+        id_public_key: Vec<u8>,
+        signature: Vec<u8>,
+        proof_of_possession: Vec<u8>,
+    },
+    CommitDkg {
+        id_address: String,
+        group_index: GroupIndex,
+        group_epoch: Epoch,
+        public_key: Vec<u8>,
+        partial_public_key: Vec<u8>,
+        disqualified_nodes: Vec<String>,
+    },
+    Request {
+        requester: String,
+        // logs recorded before requests carried a seed have the message as a string
+        #[serde(alias = "message", deserialize_with = "bytes_or_string")]
+        seed: Vec<u8>,
+        num_words: usize,
+    },
+    Fulfill {
+        id_address: String,
+        signature_index: TaskIndex,
+        signature: Vec<u8>,
+        partial_signatures: HashMap<String, Vec<u8>>,
+    },
+    ChallengeReward {
+        id_address: String,
+        signature_index: TaskIndex,
+    },
+    DisbandGroup {
+        id_address: String,
+        group_index: GroupIndex,
+    },
+    CheckDkgState {
+        id_address: String,
+        group_index: GroupIndex,
+    },
+    SetGroupConfig {
+        id_address: String,
+        config: GroupConfig,
+    },
+    CreateGroup {
+        id_address: String,
+        config: GroupConfig,
+    },
+    BlacklistNode {
+        id_address: String,
+        node: String,
+    },
+    UnblacklistNode {
+        id_address: String,
+        node: String,
+    },
+    NodeHeartbeat {
+        id_address: String,
+    },
+    NodeActivate {
+        id_address: String,
+    },
+    Mine {
+        block_number: usize,
+    },
+    Mint {
+        to: String,
+        amount: usize,
+    },
+    Approve {
+        owner: String,
+        spender: String,
+        amount: usize,
+    },
+    Claim {
+        id_address: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub transaction: Transaction,
+    pub result: bool,
+    /// The state hash of the controller right after the transaction. It is a digest of
+    /// the state's encoding, so a log keeps replaying after a toolchain upgrade.
+    pub state_hash: u64,
+}
+
+/// Everything needed to rebuild a controller: how it was created and the
+/// transactions applied to it since, in order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionLog {
+    pub owner: String,
+    pub initial_entropy: u64,
+    pub entries: Vec<LogEntry>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ReplayError {
+    #[error("entry {index} returned {actual} on replay, {expected} was recorded")]
+    ResultMismatch {
+        index: usize,
+        expected: bool,
+        actual: bool,
+    },
+    #[error("state diverged at entry {index}: recorded hash {expected:x}, replayed {actual:x}")]
+    StateDiverged {
+        index: usize,
+        expected: u64,
+        actual: u64,
+    },
+}
+
+/// Wraps a controller and appends every transaction applied through it to a log.
+/// The controller is created with the default configuration, which replay assumes.
+pub struct Recorder {
+    controller: Controller,
+    log: TransactionLog,
+}
+
+impl Recorder {
+    pub fn new(owner: String, initial_entropy: u64) -> Self {
+        Recorder {
+            controller: Controller::new(owner.clone(), initial_entropy),
+            log: TransactionLog {
+                owner,
+                initial_entropy,
+                entries: vec![],
+            },
+        }
+    }
+
+    pub fn controller(&self) -> &Controller {
+        &self.controller
+    }
+
+    pub fn log(&self) -> &TransactionLog {
+        &self.log
+    }
+
+    pub fn mine(&mut self, block_number: usize) {
+        self.apply(Transaction::Mine { block_number });
+    }
+
+    pub fn mint(&mut self, to: String, amount: usize) {
+        self.apply(Transaction::Mint { to, amount });
+    }
+
+    pub fn approve(&mut self, owner: String, spender: String, amount: usize) {
+        self.apply(Transaction::Approve {
+            owner,
+            spender,
+            amount,
+        });
+    }
+
+    /// Applies any transaction to the controller and logs it.
+    pub fn apply(&mut self, transaction: Transaction) -> bool {
+        let result = apply(&mut self.controller, transaction.clone());
+
+        self.log.entries.push(LogEntry {
+            transaction,
+            result,
+            state_hash: self.controller.state_hash(),
+        });
+
+        result
+    }
+}
+
+impl Transactions for Recorder {
+    fn node_register(
+        &mut self,
+        id_address: String,
+        id_public_key: Vec<u8>,
+        endpoint: String,
+        reward_address: String,
+        capabilities: Capabilities,
+    ) -> bool {
+        self.apply(Transaction::NodeRegister {
+            id_address,
+            id_public_key,
+            endpoint,
+            reward_address,
+            capabilities,
+        })
+    }
+
+    fn update_node_key(
+        &mut self,
+        id_address: String,
+        id_public_key: Vec<u8>,
+        signature: Vec<u8>,
+        proof_of_possession: Vec<u8>,
+    ) -> bool {
+        self.apply(Transaction::UpdateNodeKey {
+            id_address,
+            id_public_key,
+            signature,
+            proof_of_possession,
+        })
+    }
+
+    fn claim(&mut self, id_address: String) -> bool {
+        self.apply(Transaction::Claim { id_address })
+    }
+
+    fn node_activate(&mut self, id_address: String) -> bool {
+        self.apply(Transaction::NodeActivate { id_address })
+    }
+
+    // not recorded until the controller implements them
+
+    fn node_quit(&mut self, id_address: String) {
+        self.controller.node_quit(id_address)
+    }
+
+    fn redeem(&mut self, id_address: String) {
+        self.controller.redeem(id_address)
+    }
+
+    fn commit_dkg(
+        &mut self,
+        id_address: String,
+        group_index: GroupIndex,
+        group_epoch: Epoch,
+        public_key: Vec<u8>,
+        partial_public_key: Vec<u8>,
+        disqualified_nodes: Vec<String>,
+    ) -> bool {
+        self.apply(Transaction::CommitDkg {
+            id_address,
+            group_index,
+            group_epoch,
+            public_key,
+            partial_public_key,
+            disqualified_nodes,
+        })
+    }
+
+    fn request(&mut self, requester: String, seed: Vec<u8>, num_words: usize) -> bool {
+        self.apply(Transaction::Request {
+            requester,
+            seed,
+            num_words,
+        })
+    }
+
+    fn fulfill(
+        &mut self,
+        id_address: String,
+        signature_index: TaskIndex,
+        signature: Vec<u8>,
+        partial_signatures: HashMap<String, Vec<u8>>,
+    ) -> bool {
+        self.apply(Transaction::Fulfill {
+            id_address,
+            signature_index,
+            signature,
+            partial_signatures,
+        })
+    }
+
+    fn challenge_reward(&mut self, id_address: String, signature_index: TaskIndex) -> bool {
+        self.apply(Transaction::ChallengeReward {
+            id_address,
+            signature_index,
+        })
+    }
+
+    fn disband_group(&mut self, id_address: String, group_index: GroupIndex) -> bool {
+        self.apply(Transaction::DisbandGroup {
+            id_address,
+            group_index,
+        })
+    }
+
+    fn check_dkg_state(&mut self, id_address: String, group_index: GroupIndex) -> bool {
+        self.apply(Transaction::CheckDkgState {
+            id_address,
+            group_index,
+        })
+    }
+
+    fn set_group_config(&mut self, id_address: String, config: GroupConfig) -> bool {
+        self.apply(Transaction::SetGroupConfig { id_address, config })
+    }
+
+    fn create_group(&mut self, id_address: String, config: GroupConfig) -> bool {
+        self.apply(Transaction::CreateGroup { id_address, config })
+    }
+
+    fn blacklist_node(&mut self, id_address: String, node: String) -> bool {
+        self.apply(Transaction::BlacklistNode { id_address, node })
+    }
+
+    fn unblacklist_node(&mut self, id_address: String, node: String) -> bool {
+        self.apply(Transaction::UnblacklistNode { id_address, node })
+    }
+
+    fn node_heartbeat(&mut self, id_address: String) -> bool {
+        self.apply(Transaction::NodeHeartbeat { id_address })
+    }
+}
+
+/// Rebuilds the controller from the first `until` entries of the log, checking
+/// every result and state hash against the recorded ones along the way.
+pub fn replay(log: &TransactionLog, until: usize) -> Result<Controller, ReplayError> {
+    let mut controller = Controller::new(log.owner.clone(), log.initial_entropy);
+
+    for (index, entry) in log.entries.iter().take(until).enumerate() {
+        let result = apply(&mut controller, entry.transaction.clone());
+
+        if result != entry.result {
+            return Err(ReplayError::ResultMismatch {
+                index,
+                expected: entry.result,
+                actual: result,
+            });
+        }
+
+        let state_hash = controller.state_hash();
+
+        if state_hash != entry.state_hash {
+            return Err(ReplayError::StateDiverged {
+                index,
+                expected: entry.state_hash,
+                actual: state_hash,
+            });
+        }
+    }
+
+    Ok(controller)
+}
+
+fn apply(controller: &mut Controller, transaction: Transaction) -> bool {
+    match transaction {
+        Transaction::NodeRegister {
+            id_address,
+            id_public_key,
+            endpoint,
+            reward_address,
+            capabilities,
+        } => controller.node_register(
+            id_address,
+            id_public_key,
+            endpoint,
+            reward_address,
+            capabilities,
+        ),
+        Transaction::UpdateNodeKey {
+            id_address,
+            id_public_key,
+            signature,
+            proof_of_possession,
+        } => controller.update_node_key(id_address, id_public_key, signature, proof_of_possession),
+        Transaction::CommitDkg {
+            id_address,
+            group_index,
+            group_epoch,
+            public_key,
+            partial_public_key,
+            disqualified_nodes,
+        } => controller.commit_dkg(
+            id_address,
+            group_index,
+            group_epoch,
+            public_key,
+            partial_public_key,
+            disqualified_nodes,
+        ),
+        Transaction::Request {
+            requester,
+            seed,
+            num_words,
+        } => controller.request(requester, seed, num_words),
+        Transaction::Fulfill {
+            id_address,
+            signature_index,
+            signature,
+            partial_signatures,
+        } => controller.fulfill(id_address, signature_index, signature, partial_signatures),
+        Transaction::ChallengeReward {
+            id_address,
+            signature_index,
+        } => controller.challenge_reward(id_address, signature_index),
+        Transaction::DisbandGroup {
+            id_address,
+            group_index,
+        } => controller.disband_group(id_address, group_index),
+        Transaction::CheckDkgState {
+            id_address,
+            group_index,
+        } => controller.check_dkg_state(id_address, group_index),
+        Transaction::SetGroupConfig { id_address, config } => {
+            controller.set_group_config(id_address, config)
+        }
+        Transaction::CreateGroup { id_address, config } => {
+            controller.create_group(id_address, config)
+        }
+        Transaction::BlacklistNode { id_address, node } => {
+            controller.blacklist_node(id_address, node)
+        }
+        Transaction::UnblacklistNode { id_address, node } => {
+            controller.unblacklist_node(id_address, node)
+        }
+        Transaction::NodeHeartbeat { id_address } => controller.node_heartbeat(id_address),
+        Transaction::NodeActivate { id_address } => controller.node_activate(id_address),
+        Transaction::Mine { block_number } => {
+            controller.mine(block_number);
+
+            true
+        }
+        Transaction::Mint { to, amount } => {
+            controller.token.mint(&to, amount);
+
+            true
+        }
+        Transaction::Approve {
+            owner,
+            spender,
+            amount,
+        } => {
+            controller.token.approve(&owner, &spender, amount);
+
+            true
+        }
+        Transaction::Claim { id_address } => controller.claim(id_address),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::contract::{request_fee, CONTROLLER_ADDRESS, NODE_STAKING_AMOUNT};
+
+    fn record() -> Recorder {
+        let mut recorder = Recorder::new(String::from("0xadmin"), 0x8762_4875_6548_6346);
+
+        (0..4).for_each(|i| {
+            recorder.mint(format!("0x{}", i), NODE_STAKING_AMOUNT);
+
+            recorder.approve(
+                format!("0x{}", i),
+                String::from(CONTROLLER_ADDRESS),
+                NODE_STAKING_AMOUNT,
+            );
+
+            recorder.node_register(
+                format!("0x{}", i),
+                vec![],
+                String::from(""),
+                format!("0x{}", i),
+                Capabilities::default(),
+            );
+        });
+
+        let dkg_task = (*recorder.controller().emit_dkg_task(0).last().unwrap()).clone();
+
+        let mut members = dkg_task.members.keys().cloned().collect::<Vec<_>>();
+
+        members.sort();
+
+        for id_address in members {
+            recorder.commit_dkg(
+                id_address,
+                dkg_task.group_index,
+                dkg_task.epoch,
+                vec![1],
+                vec![],
+                vec![],
+            );
+        }
+
+        recorder.mint(String::from("0xrequester"), request_fee(1));
+
+        recorder.approve(
+            String::from("0xrequester"),
+            String::from(CONTROLLER_ADDRESS),
+            request_fee(1),
+        );
+
+        recorder.request(String::from("0xrequester"), b"hello".to_vec(), 1);
+
+        recorder.mine(1);
+
+        recorder.disband_group(String::from("0x1"), GroupIndex(1));
+
+        recorder.disband_group(String::from("0xadmin"), GroupIndex(1));
+
+        recorder
+    }
+
+    #[test]
+    fn replay_reconstructs_any_point() {
+        let recorder = record();
+
+        let log = recorder.log();
+
+        let controller = replay(log, log.entries.len()).unwrap();
+
+        assert_eq!(controller.state_hash(), recorder.controller().state_hash());
+        assert_eq!(controller.pending_signature_tasks().len(), 1);
+
+        // right after the group was formed, before the request
+        let controller = replay(log, 16).unwrap();
+
+        assert_eq!(controller.valid_group_indices(), vec![GroupIndex(1)]);
+        assert!(controller.pending_signature_tasks().is_empty());
+    }
+
+    #[test]
+    fn replay_detects_divergence() {
+        let recorder = record();
+
+        let mut log = recorder.log().clone();
+
+        log.entries[18].transaction = Transaction::Request {
+            requester: String::from("0xrequester"),
+            seed: b"tampered".to_vec(),
+            num_words: 1,
+        };
+
+        assert!(matches!(
+            replay(&log, log.entries.len()),
+            Err(ReplayError::StateDiverged { index: 18, .. })
+        ));
+    }
+
+    #[test]
+    fn log_survives_serialization() {
+        let recorder = record();
+
+        let json = serde_json::to_string(recorder.log()).unwrap();
+
+        let log: TransactionLog = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(&log, recorder.log());
+        assert!(replay(&log, log.entries.len()).is_ok());
+    }
+
+    #[test]
+    fn recorded_logs_replay_across_builds() {
+        // recorded once and kept as is, the hashes must not depend on the build
+        let json = r#"{"owner":"0xadmin","initial_entropy":9755439411885531974,"entries":[
+            {"transaction":{"Mint":{"to":"0x0","amount":50000}},"result":true,"state_hash":12818057721650611360},
+            {"transaction":{"Mine":{"block_number":1}},"result":true,"state_hash":4557041791065175250}
+        ]}"#;
+
+        let log: TransactionLog = serde_json::from_str(json).unwrap();
+
+        assert!(replay(&log, log.entries.len()).is_ok());
+    }
+
+    #[test]
+    fn legacy_requests_read_their_message_as_the_seed() {
+        let json = r#"{"Request":{"requester":"0xrequester","message":"hello","num_words":1}}"#;
+
+        let transaction: Transaction = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            transaction,
+            Transaction::Request {
+                requester: String::from("0xrequester"),
+                seed: b"hello".to_vec(),
+                num_words: 1,
+            }
+        );
+    }
+}
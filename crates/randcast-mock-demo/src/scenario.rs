@@ -0,0 +1,448 @@
+use crate::capabilities::Capabilities;
+use crate::contract::{
+    request_fee, Controller, GroupState, MockHelper, Transactions, Views, CONTROLLER_ADDRESS,
+    NODE_STAKING_AMOUNT,
+};
+use crate::types::{bytes_or_string, Epoch, GroupIndex};
+use serde::Deserialize;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// A declarative script of actions run against an in-process controller, with
+/// the state expected after each step.
+///
+/// ```yaml
+/// name: first group
+/// steps:
+///   - action: register_nodes
+///     count: 3
+///   - action: commit_dkg
+///     group_index: 1
+///     expect:
+///       valid_groups: [1]
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default = "default_owner")]
+    pub owner: String,
+    #[serde(default)]
+    pub initial_entropy: u64,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Step {
+    #[serde(flatten)]
+    pub action: Action,
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    /// Registers a single node, minting its stake first.
+    RegisterNode {
+        id_address: String,
+    },
+    /// Registers `count` nodes named `0x<n>`, numbered on from the nodes registered so far.
+    RegisterNodes {
+        count: usize,
+    },
+    Mine {
+        blocks: usize,
+    },
+    /// Requests a single random word on behalf of a requester funded with the fee.
+    Request {
+        // a string stands for its bytes, `message` is what the seed used to be called
+        #[serde(alias = "message", deserialize_with = "bytes_or_string")]
+        seed: Vec<u8>,
+    },
+    /// Every member of the latest DKG task of the group, except the `absent` ones,
+    /// commits the same public key and disqualified nodes.
+    CommitDkg {
+        group_index: GroupIndex,
+        #[serde(default)]
+        absent: Vec<String>,
+        #[serde(default)]
+        disqualified: Vec<String>,
+    },
+    DisbandGroup {
+        group_index: GroupIndex,
+        sender: Option<String>,
+    },
+}
+
+/// State checked after a step. Only the fields present in the script are checked.
+#[derive(Debug, Default, Deserialize)]
+pub struct Expectations {
+    /// The result of the transaction, for actions that return one.
+    pub success: Option<bool>,
+    pub valid_groups: Option<Vec<GroupIndex>>,
+    pub pending_signature_tasks: Option<usize>,
+    #[serde(default)]
+    pub groups: Vec<GroupExpectation>,
+    #[serde(default)]
+    pub rewards: HashMap<String, usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GroupExpectation {
+    pub index: GroupIndex,
+    pub size: Option<usize>,
+    pub epoch: Option<Epoch>,
+    pub state: Option<GroupState>,
+    pub members: Option<Vec<String>>,
+}
+
+#[derive(Debug, Error)]
+pub enum ScenarioError {
+    #[error("could not parse the scenario: {0}")]
+    Parse(#[from] serde_yaml::Error),
+    #[error("scenario `{scenario}` step {step}: group {group_index} has no DKG task")]
+    NoDKGTask {
+        scenario: String,
+        step: usize,
+        group_index: GroupIndex,
+    },
+    #[error("scenario `{scenario}` step {step}: there is no group {group_index}")]
+    NoSuchGroup {
+        scenario: String,
+        step: usize,
+        group_index: GroupIndex,
+    },
+    #[error("scenario `{scenario}` step {step}: expected {field} to be {expected}, got {actual}")]
+    Unexpected {
+        scenario: String,
+        step: usize,
+        field: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+fn default_owner() -> String {
+    String::from("0xadmin")
+}
+
+impl Scenario {
+    pub fn from_yaml(yaml: &str) -> Result<Self, ScenarioError> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Runs every step in order and returns the controller in its final state,
+    /// or the first expectation that didn't hold. Steps are numbered from 1.
+    pub fn run(&self) -> Result<Controller, ScenarioError> {
+        let mut controller = Controller::new(self.owner.clone(), self.initial_entropy);
+
+        let mut registered = 0;
+
+        for (i, step) in self.steps.iter().enumerate() {
+            let step_number = i + 1;
+
+            let success = match &step.action {
+                Action::RegisterNode { id_address } => {
+                    registered += 1;
+
+                    Some(register(&mut controller, id_address.clone()))
+                }
+                Action::RegisterNodes { count } => {
+                    let mut success = true;
+
+                    for _ in 0..*count {
+                        success &= register(&mut controller, format!("0x{}", registered));
+
+                        registered += 1;
+                    }
+
+                    Some(success)
+                }
+                Action::Mine { blocks } => {
+                    controller.mine(*blocks);
+
+                    None
+                }
+                Action::Request { seed } => Some(request(&mut controller, seed.clone())),
+                Action::CommitDkg {
+                    group_index,
+                    absent,
+                    disqualified,
+                } => {
+                    let dkg_task = controller
+                        .emit_dkg_task(0)
+                        .into_iter()
+                        .rev()
+                        .find(|task| task.group_index == *group_index)
+                        .cloned()
+                        .ok_or_else(|| ScenarioError::NoDKGTask {
+                            scenario: self.name.clone(),
+                            step: step_number,
+                            group_index: *group_index,
+                        })?;
+
+                    let mut committers = dkg_task
+                        .members
+                        .iter()
+                        .filter(|(id_address, _)| !absent.contains(id_address))
+                        .collect::<Vec<_>>();
+
+                    committers.sort_by_key(|(_, index)| **index);
+
+                    let mut success = true;
+
+                    for (id_address, _) in committers {
+                        success &= controller.commit_dkg(
+                            id_address.clone(),
+                            dkg_task.group_index,
+                            dkg_task.epoch,
+                            vec![dkg_task.epoch.0 as u8],
+                            vec![],
+                            disqualified.clone(),
+                        );
+                    }
+
+                    Some(success)
+                }
+                Action::DisbandGroup {
+                    group_index,
+                    sender,
+                } => {
+                    let sender = sender.clone().unwrap_or_else(|| self.owner.clone());
+
+                    Some(controller.disband_group(sender, *group_index))
+                }
+            };
+
+            self.check(&controller, step_number, success, &step.expect)?;
+        }
+
+        Ok(controller)
+    }
+
+    fn check(
+        &self,
+        controller: &Controller,
+        step: usize,
+        success: Option<bool>,
+        expect: &Expectations,
+    ) -> Result<(), ScenarioError> {
+        let unexpected = |field: String, expected: String, actual: String| {
+            Err(ScenarioError::Unexpected {
+                scenario: self.name.clone(),
+                step,
+                field,
+                expected,
+                actual,
+            })
+        };
+
+        if let Some(expected) = expect.success {
+            if success != Some(expected) {
+                return unexpected(
+                    String::from("success"),
+                    format!("{:?}", Some(expected)),
+                    format!("{:?}", success),
+                );
+            }
+        }
+
+        if let Some(expected) = &expect.valid_groups {
+            let mut actual = controller.valid_group_indices();
+
+            actual.sort_unstable();
+
+            if &actual != expected {
+                return unexpected(
+                    String::from("valid_groups"),
+                    format!("{:?}", expected),
+                    format!("{:?}", actual),
+                );
+            }
+        }
+
+        if let Some(expected) = expect.pending_signature_tasks {
+            let actual = controller.pending_signature_tasks().len();
+
+            if actual != expected {
+                return unexpected(
+                    String::from("pending_signature_tasks"),
+                    expected.to_string(),
+                    actual.to_string(),
+                );
+            }
+        }
+
+        for expected in &expect.groups {
+            let group = controller.find_group(expected.index).ok_or_else(|| {
+                ScenarioError::NoSuchGroup {
+                    scenario: self.name.clone(),
+                    step,
+                    group_index: expected.index,
+                }
+            })?;
+
+            let field = |name: &str| format!("group {} {}", expected.index, name);
+
+            if let Some(size) = expected.size {
+                if group.size != size {
+                    return unexpected(field("size"), size.to_string(), group.size.to_string());
+                }
+            }
+
+            if let Some(epoch) = expected.epoch {
+                if group.epoch != epoch {
+                    return unexpected(field("epoch"), epoch.to_string(), group.epoch.to_string());
+                }
+            }
+
+            if let Some(state) = expected.state {
+                if group.state != state {
+                    return unexpected(
+                        field("state"),
+                        format!("{:?}", state),
+                        format!("{:?}", group.state),
+                    );
+                }
+            }
+
+            if let Some(members) = &expected.members {
+                let mut expected_members = members.clone();
+
+                expected_members.sort();
+
+                let mut actual = group.members.keys().cloned().collect::<Vec<_>>();
+
+                actual.sort();
+
+                if actual != expected_members {
+                    return unexpected(
+                        field("members"),
+                        format!("{:?}", expected_members),
+                        format!("{:?}", actual),
+                    );
+                }
+            }
+        }
+
+        let mut rewards = expect.rewards.iter().collect::<Vec<_>>();
+
+        rewards.sort();
+
+        for (reward_address, expected) in rewards {
+            let actual = controller.rewards.get(reward_address).copied();
+
+            if actual != Some(*expected) {
+                return unexpected(
+                    format!("reward of {}", reward_address),
+                    expected.to_string(),
+                    format!("{:?}", actual),
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn register(controller: &mut Controller, id_address: String) -> bool {
+    controller.token.mint(&id_address, NODE_STAKING_AMOUNT);
+
+    controller
+        .token
+        .approve(&id_address, CONTROLLER_ADDRESS, NODE_STAKING_AMOUNT);
+
+    controller.node_register(
+        id_address.clone(),
+        vec![],
+        String::from(""),
+        id_address,
+        Capabilities::default(),
+    )
+}
+
+fn request(controller: &mut Controller, seed: Vec<u8>) -> bool {
+    let requester = String::from("0xrequester");
+
+    controller.token.mint(&requester, request_fee(1));
+
+    controller
+        .token
+        .approve(&requester, CONTROLLER_ADDRESS, request_fee(1));
+
+    controller.request(requester, seed, 1)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    const SCENARIOS: [&str; 3] = [
+        include_str!("../scenarios/form_first_group.yaml"),
+        include_str!("../scenarios/split_into_second_group.yaml"),
+        include_str!("../scenarios/disband_and_regroup.yaml"),
+    ];
+
+    #[test]
+    fn regression_scenarios_pass() {
+        for yaml in SCENARIOS.iter() {
+            let scenario = Scenario::from_yaml(yaml).unwrap();
+
+            if let Err(e) = scenario.run() {
+                panic!("{}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn failed_expectation_names_the_step() {
+        let scenario = Scenario::from_yaml(
+            "
+name: wrong size
+steps:
+  - action: register_nodes
+    count: 3
+  - action: mine
+    blocks: 1
+    expect:
+      groups:
+        - index: 1
+          size: 4
+",
+        )
+        .unwrap();
+
+        let err = scenario.run().err().unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "scenario `wrong size` step 2: expected group 1 size to be 4, got 3"
+        );
+    }
+
+    #[test]
+    fn missing_group_is_an_error() {
+        let scenario = Scenario::from_yaml(
+            "
+name: missing group
+steps:
+  - action: register_nodes
+    count: 3
+    expect:
+      groups:
+        - index: 2
+          size: 3
+",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            scenario.run(),
+            Err(ScenarioError::NoSuchGroup {
+                step: 1,
+                group_index: GroupIndex(2),
+                ..
+            })
+        ));
+    }
+}
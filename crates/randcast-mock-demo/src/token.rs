@@ -0,0 +1,133 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TokenError {
+    #[error("{0} holds {1}, {2} is needed")]
+    InsufficientBalance(String, usize, usize),
+    #[error("{0} may spend {1} on behalf of {2}, {3} is needed")]
+    InsufficientAllowance(String, usize, String, usize),
+}
+
+/// A mock of the ERC20 token staked by nodes and paid as rewards. Balances and
+/// allowances are kept in key order so that the ledger hashes deterministically.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Serialize)]
+pub struct Token {
+    balances: BTreeMap<String, usize>,
+    // keyed by (owner, spender)
+    allowances: BTreeMap<(String, String), usize>,
+}
+
+impl Token {
+    pub fn new() -> Self {
+        Token::default()
+    }
+
+    pub fn balance_of(&self, address: &str) -> usize {
+        self.balances.get(address).cloned().unwrap_or(0)
+    }
+
+    pub fn allowance(&self, owner: &str, spender: &str) -> usize {
+        self.allowances
+            .get(&(owner.to_string(), spender.to_string()))
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    /// The sum of all balances, which only minting changes.
+    pub fn total_supply(&self) -> usize {
+        self.balances.values().sum()
+    }
+
+    /// Creates `amount` tokens out of thin air, for tests and local setups only.
+    pub fn mint(&mut self, to: &str, amount: usize) {
+        *self.balances.entry(to.to_string()).or_insert(0) += amount;
+    }
+
+    pub fn transfer(&mut self, from: &str, to: &str, amount: usize) -> Result<(), TokenError> {
+        let balance = self.balance_of(from);
+
+        if balance < amount {
+            return Err(TokenError::InsufficientBalance(
+                from.to_string(),
+                balance,
+                amount,
+            ));
+        }
+
+        self.balances.insert(from.to_string(), balance - amount);
+
+        self.mint(to, amount);
+
+        Ok(())
+    }
+
+    /// Lets `spender` transfer up to `amount` from the owner's balance, replacing
+    /// any previous allowance.
+    pub fn approve(&mut self, owner: &str, spender: &str, amount: usize) {
+        self.allowances
+            .insert((owner.to_string(), spender.to_string()), amount);
+    }
+
+    pub fn transfer_from(
+        &mut self,
+        spender: &str,
+        from: &str,
+        to: &str,
+        amount: usize,
+    ) -> Result<(), TokenError> {
+        let allowance = self.allowance(from, spender);
+
+        if allowance < amount {
+            return Err(TokenError::InsufficientAllowance(
+                spender.to_string(),
+                allowance,
+                from.to_string(),
+                amount,
+            ));
+        }
+
+        self.transfer(from, to, amount)?;
+
+        self.approve(from, spender, allowance - amount);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn transfer_from_spends_the_allowance() {
+        let mut token = Token::new();
+
+        token.mint("0x0", 100);
+
+        assert_eq!(
+            token.transfer_from("0xspender", "0x0", "0x1", 60),
+            Err(TokenError::InsufficientAllowance(
+                String::from("0xspender"),
+                0,
+                String::from("0x0"),
+                60
+            ))
+        );
+
+        token.approve("0x0", "0xspender", 150);
+
+        assert!(token.transfer_from("0xspender", "0x0", "0x1", 60).is_ok());
+        assert_eq!(token.balance_of("0x0"), 40);
+        assert_eq!(token.balance_of("0x1"), 60);
+        assert_eq!(token.allowance("0x0", "0xspender"), 90);
+
+        // the allowance is left untouched when the balance falls short
+        assert_eq!(
+            token.transfer_from("0xspender", "0x0", "0x1", 60),
+            Err(TokenError::InsufficientBalance(String::from("0x0"), 40, 60))
+        );
+        assert_eq!(token.allowance("0x0", "0xspender"), 90);
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+
+macro_rules! index_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(pub usize);
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+index_type!(
+    /// The index of a group, starting at 1.
+    GroupIndex
+);
+
+index_type!(
+    /// The epoch of a group, bumped every time it runs a new DKG.
+    Epoch
+);
+
+impl Epoch {
+    pub fn next(self) -> Self {
+        Epoch(self.0 + 1)
+    }
+}
+
+index_type!(
+    /// The index of a signature task, starting at 1.
+    TaskIndex
+);
+
+index_type!(
+    /// The index of a member within its group, starting at 1. The DKG index of the
+    /// member is one less.
+    MemberIndex
+);
+
+impl MemberIndex {
+    pub fn dkg_index(self) -> u32 {
+        self.0 as u32 - 1
+    }
+}
+
+/// Deserializes bytes that may also be given as a string, standing for its UTF-8 bytes,
+/// like the messages requests were made with before they carried a seed.
+pub fn bytes_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BytesOrString {
+        Bytes(Vec<u8>),
+        String(String),
+    }
+
+    Ok(match BytesOrString::deserialize(deserializer)? {
+        BytesOrString::Bytes(bytes) => bytes,
+        BytesOrString::String(string) => string.into_bytes(),
+    })
+}
@@ -0,0 +1,229 @@
+use crate::contract::{Controller, Transactions};
+use crate::mixing::MixingFunction;
+use crate::types::TaskIndex;
+use std::collections::HashMap;
+
+/// Stands in for a contract consuming randomness through the VRF interface.
+pub trait VRFConsumer {
+    /// Called once the request is fulfilled, like `rawFulfillRandomWords`.
+    fn fulfill_random_words(&mut self, request_id: u64, random_words: Vec<u64>);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VRFRequest {
+    pub request_id: u64,
+    pub key_hash: String,
+    pub sender: String,
+    pub seed: u64,
+    pub num_words: usize,
+}
+
+/// A façade mimicking Chainlink's VRFCoordinator on top of the controller: every
+/// request becomes a signature task, and fulfilling the task hands the random
+/// words derived from the group signature back to the consumer.
+pub struct VRFCoordinator {
+    controller: Controller,
+    // keyed by the index of the signature task serving the request
+    requests: HashMap<TaskIndex, VRFRequest>,
+    nonces: HashMap<String, u64>,
+}
+
+impl VRFCoordinator {
+    pub fn new(controller: Controller) -> Self {
+        VRFCoordinator {
+            controller,
+            requests: HashMap::new(),
+            nonces: HashMap::new(),
+        }
+    }
+
+    pub fn controller(&self) -> &Controller {
+        &self.controller
+    }
+
+    pub fn controller_mut(&mut self) -> &mut Controller {
+        &mut self.controller
+    }
+
+    /// Returns the pending request served by the signature task.
+    pub fn get_request(&self, signature_index: TaskIndex) -> Option<&VRFRequest> {
+        self.requests.get(&signature_index)
+    }
+
+    /// Requests `num_words` random words. The request id is derived like the
+    /// VRFCoordinator does, from the key hash and a seed made of the key hash,
+    /// the sender and the sender's nonce. The sender pays the request fee. Returns
+    /// None if no group can sign or the fee can't be paid.
+    pub fn request_random_words(
+        &mut self,
+        sender: String,
+        key_hash: String,
+        num_words: usize,
+    ) -> Option<u64> {
+        let nonce = self.nonces.get(&sender).cloned().unwrap_or(0);
+
+        let mixing = self.controller.mixing;
+
+        let seed = mixing.mix(&(&key_hash, &sender, nonce));
+
+        let request_id = mixing.mix(&(&key_hash, seed));
+
+        if !self
+            .controller
+            .request(sender.clone(), seed.to_be_bytes().to_vec(), num_words)
+        {
+            return None;
+        }
+
+        self.nonces.insert(sender.clone(), nonce + 1);
+
+        self.requests.insert(
+            TaskIndex(self.controller.signature_count),
+            VRFRequest {
+                request_id,
+                key_hash,
+                sender,
+                seed,
+                num_words,
+            },
+        );
+
+        Some(request_id)
+    }
+
+    /// Fulfills the signature task through the controller and, if it accepts the
+    /// signature, calls back the consumer with the words derived from it.
+    pub fn fulfill(
+        &mut self,
+        id_address: String,
+        signature_index: TaskIndex,
+        signature: Vec<u8>,
+        partial_signatures: HashMap<String, Vec<u8>>,
+        consumer: &mut dyn VRFConsumer,
+    ) -> bool {
+        if !self.requests.contains_key(&signature_index) {
+            return false;
+        }
+
+        if !self.controller.fulfill(
+            id_address,
+            signature_index,
+            signature.clone(),
+            partial_signatures,
+        ) {
+            return false;
+        }
+
+        let request = self.requests.remove(&signature_index).unwrap();
+
+        consumer.fulfill_random_words(
+            request.request_id,
+            random_words(self.controller.mixing, &signature, request.num_words),
+        );
+
+        true
+    }
+}
+
+/// Expands the signature into `num_words` words, each one hashed with its position.
+pub fn random_words(mixing: MixingFunction, signature: &[u8], num_words: usize) -> Vec<u64> {
+    let randomness = mixing.mix(signature);
+
+    (0..num_words)
+        .map(|i| mixing.mix(&(randomness, i as u64)))
+        .collect::<Vec<_>>()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::contract::tests::{
+        commit_latest_dkg_task_with_keys, register_nodes, sign_partially,
+    };
+    use crate::contract::{request_fee, Views, CONTROLLER_ADDRESS};
+    use crate::types::GroupIndex;
+    use threshold_bls::schemes::bls12_381::G1Scheme as SigScheme;
+    use threshold_bls::sig::SignatureScheme;
+
+    #[derive(Default)]
+    struct Consumer {
+        fulfilled: Vec<(u64, Vec<u64>)>,
+    }
+
+    impl VRFConsumer for Consumer {
+        fn fulfill_random_words(&mut self, request_id: u64, random_words: Vec<u64>) {
+            self.fulfilled.push((request_id, random_words));
+        }
+    }
+
+    #[test]
+    fn request_is_fulfilled_through_signature_task() {
+        let mut controller = Controller::new(String::from("0xadmin"), 0x8762_4875_6548_6346);
+
+        register_nodes(&mut controller, 3);
+
+        let (private_key, member_keys) = commit_latest_dkg_task_with_keys(&mut controller);
+
+        controller.token.mint("0xconsumer", 2 * request_fee(2));
+
+        controller
+            .token
+            .approve("0xconsumer", CONTROLLER_ADDRESS, 2 * request_fee(2));
+
+        let mut coordinator = VRFCoordinator::new(controller);
+
+        let key_hash = String::from("0xkeyhash");
+
+        let first = coordinator
+            .request_random_words(String::from("0xconsumer"), key_hash.clone(), 2)
+            .unwrap();
+
+        let second = coordinator
+            .request_random_words(String::from("0xconsumer"), key_hash, 2)
+            .unwrap();
+
+        // the nonce makes every request from the same sender unique
+        assert_ne!(first, second);
+
+        let task = coordinator.controller().pending_signature_tasks()[0].clone();
+
+        assert_eq!(
+            coordinator.get_request(task.index).unwrap().request_id,
+            first
+        );
+
+        let committer = coordinator.controller().get_group(GroupIndex(1)).committers[0].clone();
+
+        let signature = SigScheme::sign(&private_key, &task.message()).unwrap();
+
+        let partial_signatures = sign_partially(&member_keys, &task.message());
+
+        let mut consumer = Consumer::default();
+
+        assert!(!coordinator.fulfill(
+            committer.clone(),
+            task.index,
+            vec![],
+            HashMap::new(),
+            &mut consumer
+        ));
+        assert!(consumer.fulfilled.is_empty());
+
+        assert!(coordinator.fulfill(
+            committer,
+            task.index,
+            signature.clone(),
+            partial_signatures,
+            &mut consumer
+        ));
+
+        assert_eq!(
+            consumer.fulfilled,
+            vec![(
+                first,
+                random_words(MixingFunction::default(), &signature, 2)
+            )]
+        );
+        assert!(coordinator.get_request(task.index).is_none());
+    }
+}
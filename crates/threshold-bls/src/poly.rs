@@ -103,6 +103,59 @@ pub enum PolyError {
     NoInverse,
 }
 
+/// A small cache of the lagrange coefficients of the last sets of indices a value was
+/// recovered from. The coefficients only depend on the indices, so a threshold signature
+/// aggregated from the same signers as a recent one skips the inversions.
+#[derive(Debug, Clone)]
+pub struct LagrangeCache<X> {
+    capacity: usize,
+    // least recently used first
+    entries: Vec<(Vec<Idx>, Vec<X>)>,
+}
+
+impl<X: Scalar<RHS = X>> LagrangeCache<X> {
+    /// Creates a cache holding the coefficients of up to `capacity` sets of indices
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the number of sets of indices held
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the coefficients of the sorted `indices`, evicting the least recently
+    /// used set if they have to be computed and the cache is full
+    pub fn coefficients(&mut self, indices: Vec<Idx>) -> Result<&[X], PolyError> {
+        match self
+            .entries
+            .iter()
+            .position(|(cached, _)| *cached == indices)
+        {
+            Some(position) => {
+                let entry = self.entries.remove(position);
+                self.entries.push(entry);
+            }
+            None => {
+                let coefficients = Poly::<X>::lagrange_coefficients(&indices)?;
+                if self.entries.len() == self.capacity {
+                    self.entries.remove(0);
+                }
+                self.entries.push((indices, coefficients));
+            }
+        }
+
+        Ok(self.entries.last().unwrap().1.as_slice())
+    }
+}
+
 impl<C> Poly<C>
 where
     C: Element,
@@ -133,35 +186,37 @@ where
     pub fn recover(t: usize, shares: Vec<Eval<C>>) -> Result<C, PolyError> {
         let xs = Self::share_map(t, shares)?;
 
-        // iterate over all indices and for each multiply the lagrange basis
-        // with the value of the share
-        let mut acc = C::zero();
-        for (i, xi) in &xs {
-            let mut yi = xi.1.clone();
-            let mut num = C::RHS::one();
-            let mut den = C::RHS::one();
-
-            for (j, xj) in &xs {
-                if i == j {
-                    continue;
-                }
+        let indices = xs.keys().cloned().collect::<Vec<_>>();
+        let coefficients = Poly::<C::RHS>::lagrange_coefficients(&indices)?;
 
-                // xj - 0
-                num.mul(&xj.0);
+        Ok(Self::linear_combination(&xs, &coefficients))
+    }
 
-                // 1 / (xj - xi)
-                let mut tmp = xj.0.clone();
-                tmp.sub(&xi.0);
-                den.mul(&tmp);
-            }
+    /// Same as `recover`, but takes the lagrange coefficients of the `t` shares used
+    /// from the cache, computing them only for a set of indices it doesn't hold yet
+    pub fn recover_cached(
+        t: usize,
+        shares: Vec<Eval<C>>,
+        cache: &mut LagrangeCache<C::RHS>,
+    ) -> Result<C, PolyError> {
+        let xs = Self::share_map(t, shares)?;
+
+        let indices = xs.keys().cloned().collect::<Vec<_>>();
+        let coefficients = cache.coefficients(indices)?;
 
-            let inv = den.inverse().ok_or(PolyError::NoInverse)?;
-            num.mul(&inv);
-            yi.mul(&num);
+        Ok(Self::linear_combination(&xs, coefficients))
+    }
+
+    // multiplies the value of each share with its lagrange coefficient and sums them
+    fn linear_combination(xs: &BTreeMap<Idx, (C::RHS, C)>, coefficients: &[C::RHS]) -> C {
+        let mut acc = C::zero();
+        for ((_, yi), coefficient) in xs.values().zip(coefficients) {
+            let mut yi = yi.clone();
+            yi.mul(coefficient);
             acc.add(&yi);
         }
 
-        Ok(acc)
+        acc
     }
 
     /// Given at least `t` polynomial evaluations, it will recover the entire polynomial
@@ -284,6 +339,45 @@ impl<X: Scalar<RHS = X>> Poly<X> {
         Poly::from(vec![c, X::one()])
     }
 
+    /// Computes the lagrange coefficients at 0 of the shares with the given distinct
+    /// indices, in the same order
+    pub fn lagrange_coefficients(indices: &[Idx]) -> Result<Vec<X>, PolyError> {
+        let xs = indices
+            .iter()
+            .map(|i| {
+                let mut xi = X::new();
+                xi.set_int((i + 1).into());
+                xi
+            })
+            .collect::<Vec<_>>();
+
+        xs.iter()
+            .enumerate()
+            .map(|(i, xi)| {
+                let mut num = X::one();
+                let mut den = X::one();
+
+                for (j, xj) in xs.iter().enumerate() {
+                    if i == j {
+                        continue;
+                    }
+
+                    // xj - 0
+                    num.mul(xj);
+
+                    // 1 / (xj - xi)
+                    let mut tmp = xj.clone();
+                    tmp.sub(xi);
+                    den.mul(&tmp);
+                }
+
+                let inv = den.inverse().ok_or(PolyError::NoInverse)?;
+                num.mul(&inv);
+                Ok(num)
+            })
+            .collect::<Result<_, PolyError>>()
+    }
+
     /// Computes the lagrange basis polynomial of index i
     fn lagrange_basis<E: Element<RHS = X>>(i: Idx, xs: &BTreeMap<Idx, (X, E)>) -> Poly<X> {
         let mut basis = Poly::<X>::from(vec![X::one()]);
@@ -534,6 +628,66 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn cached_recover() {
+        let degree = 4;
+        let threshold = degree + 1;
+        let poly = Poly::<Sc>::new(degree);
+        let mut cache = LagrangeCache::new(2);
+
+        for signers in &[
+            [0, 1, 2, 3, 4],
+            [1, 3, 5, 6, 8],
+            [0, 1, 2, 3, 4],
+            [2, 3, 4, 5, 6],
+        ] {
+            let shares = signers
+                .iter()
+                .map(|i| poly.eval(*i as Idx))
+                .collect::<Vec<Eval<Sc>>>();
+            let recovered = Poly::<Sc>::recover_cached(threshold, shares, &mut cache).unwrap();
+            assert_eq!(recovered, poly.0[0]);
+        }
+
+        // the second set was the least recently used
+        assert_eq!(cache.len(), 2);
+        assert!(cache
+            .entries
+            .iter()
+            .all(|(indices, _)| *indices != vec![1, 3, 5, 6, 8]));
+    }
+
+    #[test]
+    fn cached_recover_benchy() {
+        use std::time::SystemTime;
+        let degree = 49;
+        let threshold = degree + 1;
+        let rounds = 20;
+        let poly = Poly::<Sc>::new(degree);
+        let shares = (0..threshold)
+            .map(|i| poly.eval(i as Idx))
+            .collect::<Vec<Eval<Sc>>>();
+
+        let now = SystemTime::now();
+        for _ in 0..rounds {
+            Poly::<Sc>::recover(threshold, shares.clone()).unwrap();
+        }
+        match now.elapsed() {
+            Ok(e) => println!("{} recovers: time elapsed {:?}", rounds, e),
+            Err(e) => panic!("{}", e),
+        }
+
+        let mut cache = LagrangeCache::new(1);
+        let now = SystemTime::now();
+        for _ in 0..rounds {
+            Poly::<Sc>::recover_cached(threshold, shares.clone(), &mut cache).unwrap();
+        }
+        match now.elapsed() {
+            Ok(e) => println!("{} cached recovers: time elapsed {:?}", rounds, e),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
     #[test]
     fn mul() {
         let d = 1;
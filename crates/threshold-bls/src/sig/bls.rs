@@ -16,6 +16,9 @@ pub enum BLSError {
 
     #[error("could not deserialize: {0}")]
     DeserializationError(#[from] bincode::Error),
+
+    #[error("{0} public keys were given for {1} signatures")]
+    LengthMismatch(usize, usize),
 }
 
 // private module workaround to avoid leaking a private
@@ -72,6 +75,43 @@ pub mod common {
             Ok(())
         }
 
+        /// Checks e(g, sum(r_i * sig_i)) == e(sum(r_i * pub_i), H(m)) for random r_i, which
+        /// keeps signatures from cancelling each other out in the sums.
+        fn internal_aggregation_verify(
+            publics: &[Self::Public],
+            msg: &[u8],
+            sigs: &[&[u8]],
+        ) -> Result<(), BLSError> {
+            if publics.len() != sigs.len() {
+                return Err(BLSError::LengthMismatch(publics.len(), sigs.len()));
+            }
+
+            let mut h = Self::Signature::new();
+            h.map(msg).map_err(|_| BLSError::HashingError)?;
+
+            let rng = &mut rand::thread_rng();
+            let mut public = Self::Public::new();
+            let mut sig = Self::Signature::new();
+            for (p, sig_bytes) in publics.iter().zip(sigs) {
+                let r = Self::Private::rand(rng);
+
+                let mut p = p.clone();
+                p.mul(&r);
+                public.add(&p);
+
+                let mut s: Self::Signature = bincode::deserialize_from(*sig_bytes)?;
+                s.mul(&r);
+                sig.add(&s);
+            }
+
+            let success = Self::final_exp(&public, &sig, &h);
+            if !success {
+                return Err(BLSError::InvalidSig);
+            }
+
+            Ok(())
+        }
+
         /// Performs the final exponentiation for the BLS sig scheme
         fn final_exp(p: &Self::Public, sig: &Self::Signature, hm: &Self::Signature) -> bool;
     }
@@ -94,6 +134,14 @@ pub mod common {
         ) -> Result<(), Self::Error> {
             T::internal_verify(public, msg_bytes, sig_bytes, true)
         }
+
+        fn aggregation_verify_on_the_same_msg(
+            publics: &[Self::Public],
+            msg: &[u8],
+            sigs: &[&[u8]],
+        ) -> Result<(), Self::Error> {
+            T::internal_aggregation_verify(publics, msg, sigs)
+        }
     }
 }
 
@@ -185,4 +233,26 @@ mod tests {
         let sig = G1Scheme::<PCurve>::sign(&private, &msg).unwrap();
         G1Scheme::<PCurve>::verify(&public, &msg, &sig).expect("that should not happen");
     }
+
+    #[test]
+    fn aggregation_verify_on_the_same_msg_g1() {
+        let msg = vec![1, 9, 6, 9];
+        let (privates, publics): (Vec<_>, Vec<_>) = (0..4).map(|_| keypair::<G1Curve>()).unzip();
+        let mut sigs = privates
+            .iter()
+            .map(|private| G1Scheme::<PCurve>::sign(private, &msg).unwrap())
+            .collect::<Vec<_>>();
+
+        let sig_refs = sigs.iter().map(|sig| sig.as_slice()).collect::<Vec<_>>();
+        G1Scheme::<PCurve>::aggregation_verify_on_the_same_msg(&publics, &msg, &sig_refs)
+            .expect("all signatures are valid");
+
+        // a signature over another message spoils the whole batch
+        sigs[2] = G1Scheme::<PCurve>::sign(&privates[2], &[0]).unwrap();
+        let sig_refs = sigs.iter().map(|sig| sig.as_slice()).collect::<Vec<_>>();
+        assert!(
+            G1Scheme::<PCurve>::aggregation_verify_on_the_same_msg(&publics, &msg, &sig_refs)
+                .is_err()
+        );
+    }
 }
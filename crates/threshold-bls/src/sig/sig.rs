@@ -1,7 +1,7 @@
 pub use super::tbls::Share; // import and re-export it for easier access
 use crate::{
     group::{Element, Point, Scalar},
-    poly::Poly,
+    poly::{LagrangeCache, Poly},
 };
 use rand_core::RngCore;
 use serde::{de::DeserializeOwned, Serialize};
@@ -63,6 +63,15 @@ pub trait SignatureScheme: Scheme {
 
     /// Verifies that the signature on the provided message was produced by the public key
     fn verify(public: &Self::Public, msg: &[u8], sig: &[u8]) -> Result<(), Self::Error>;
+
+    /// Verifies that each signature on the provided message was produced by the public key
+    /// at the same position, with a single pairing check for all of them. It fails as a
+    /// whole, so finding the invalid signature still takes a `verify` per signature.
+    fn aggregation_verify_on_the_same_msg(
+        publics: &[Self::Public],
+        msg: &[u8],
+        sigs: &[&[u8]],
+    ) -> Result<(), Self::Error>;
 }
 
 /// BlindScheme is a signature scheme where the message can be blinded before
@@ -158,6 +167,14 @@ pub trait ThresholdScheme: Scheme {
     /// not verify if the partial signatures are correct or not; it only
     /// aggregates them.
     fn aggregate(threshold: usize, partials: &[Partial]) -> Result<Vec<u8>, Self::Error>;
+
+    /// Same as `aggregate`, reusing the lagrange coefficients of the signers from the
+    /// cache when the same set of signers aggregated recently
+    fn aggregate_cached(
+        threshold: usize,
+        partials: &[Partial],
+        cache: &mut LagrangeCache<Self::Private>,
+    ) -> Result<Vec<u8>, Self::Error>;
 }
 
 /// BlindThreshold is ThresholdScheme that allows to verify a partially blinded
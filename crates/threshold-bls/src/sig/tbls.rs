@@ -1,6 +1,6 @@
 //! Threshold Signatures implementation for any type which implements
 //! [`SignatureScheme`](../trait.SignatureScheme.html)
-use crate::poly::{Eval, Idx, Poly, PolyError};
+use crate::poly::{Eval, Idx, LagrangeCache, Poly, PolyError};
 use crate::sig::{Partial, SignatureScheme, ThresholdScheme};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -67,29 +67,49 @@ impl<I: SignatureScheme> ThresholdScheme for I {
         threshold: usize,
         partials: &[Partial],
     ) -> Result<Vec<u8>, <Self as ThresholdScheme>::Error> {
-        if threshold > partials.len() {
-            return Err(ThresholdError::NotEnoughPartialSignatures(
-                partials.len(),
-                threshold,
-            ));
-        }
-
-        let valid_partials: Vec<Eval<Self::Signature>> = partials
-            .iter()
-            .map(|partial| {
-                let eval: Eval<Vec<u8>> = bincode::deserialize(&partial)?;
-                let sig = bincode::deserialize(&eval.value)?;
-                Ok(Eval {
-                    index: eval.index,
-                    value: sig,
-                })
-            })
-            .collect::<Result<_, <Self as ThresholdScheme>::Error>>()?;
+        let valid_partials = deserialize_partials::<Self>(threshold, partials)?;
 
         let recovered_sig = Poly::<Self::Signature>::recover(threshold, valid_partials)
             .map_err(ThresholdError::PolyError)?;
         Ok(bincode::serialize(&recovered_sig).expect("could not serialize"))
     }
+
+    fn aggregate_cached(
+        threshold: usize,
+        partials: &[Partial],
+        cache: &mut LagrangeCache<Self::Private>,
+    ) -> Result<Vec<u8>, <Self as ThresholdScheme>::Error> {
+        let valid_partials = deserialize_partials::<Self>(threshold, partials)?;
+
+        let recovered_sig =
+            Poly::<Self::Signature>::recover_cached(threshold, valid_partials, cache)
+                .map_err(ThresholdError::PolyError)?;
+        Ok(bincode::serialize(&recovered_sig).expect("could not serialize"))
+    }
+}
+
+fn deserialize_partials<I: SignatureScheme>(
+    threshold: usize,
+    partials: &[Partial],
+) -> Result<Vec<Eval<I::Signature>>, ThresholdError<I>> {
+    if threshold > partials.len() {
+        return Err(ThresholdError::NotEnoughPartialSignatures(
+            partials.len(),
+            threshold,
+        ));
+    }
+
+    partials
+        .iter()
+        .map(|partial| {
+            let eval: Eval<Vec<u8>> = bincode::deserialize(&partial)?;
+            let sig = bincode::deserialize(&eval.value)?;
+            Ok(Eval {
+                index: eval.index,
+                value: sig,
+            })
+        })
+        .collect::<Result<_, ThresholdError<I>>>()
 }
 
 #[cfg(feature = "bls12_381")]
@@ -0,0 +1,178 @@
+// Using the ABIEncoderV2 poses little risk here because we only use it for fetching the byte arrays
+// of shares/responses/justifications and for initializing the participants' keys
+pragma experimental ABIEncoderV2;
+pragma solidity ^0.6.6;
+
+/// A DKG board for one epoch of one group. Unlike the `DKG` contract, participants do not
+/// register themselves: the owner (e.g. the randcast controller) initializes the board with
+/// the group members and the BLS keys they registered with, which also starts the DKG.
+contract Coordinator {
+    /// Mapping of Ethereum Address => BLS public keys
+    mapping(address => bytes) public keys;
+
+    /// Mapping of Ethereum Address => DKG Phase 1 Shares
+    mapping(address => bytes) public shares;
+
+    /// Mapping of Ethereum Address => DKG Phase 2 Responses
+    mapping(address => bytes) public responses;
+
+    /// Mapping of Ethereum Address => DKG Phase 3 Justifications
+    mapping(address => bytes) public justifications;
+
+    /// List of participants, in DKG index order
+    address[] public participants;
+
+    /// The duration of each phase
+    uint256 public immutable PHASE_DURATION;
+
+    /// The threshold of the DKG
+    uint256 public immutable THRESHOLD;
+
+    /// The index of the group running the DKG
+    uint256 public immutable GROUP_INDEX;
+
+    /// The epoch of the group the DKG is run for
+    uint256 public immutable GROUP_EPOCH;
+
+    /// If it's 0 then the DKG is still pending start. If >0, it is the DKG's start block
+    uint256 public startBlock = 0;
+
+    /// The owner of the board is the address which can call the `initialize` function
+    address public owner;
+
+    /// A participant is one whose pubkey's length > 0
+    modifier onlyParticipant() {
+        require(keys[msg.sender].length > 0, "you are not a participant!");
+        _;
+    }
+
+    constructor(
+        uint256 threshold,
+        uint256 duration,
+        uint256 groupIndex,
+        uint256 groupEpoch
+    ) public {
+        PHASE_DURATION = duration;
+        THRESHOLD = threshold;
+        GROUP_INDEX = groupIndex;
+        GROUP_EPOCH = groupEpoch;
+        owner = msg.sender;
+    }
+
+    /// Sets the participants along with their BLS public keys and starts the DKG
+    function initialize(address[] calldata nodes, bytes[] calldata blsPublicKeys) external {
+        require(msg.sender == owner, "only owner may initialize the DKG");
+        require(startBlock == 0, "DKG has already started");
+        require(nodes.length == blsPublicKeys.length, "nodes and keys length mismatch");
+
+        for (uint256 i = 0; i < nodes.length; i++) {
+            require(blsPublicKeys[i].length > 0, "empty BLS public key");
+            require(keys[nodes[i]].length == 0, "duplicate participant");
+
+            participants.push(nodes[i]);
+            keys[nodes[i]] = blsPublicKeys[i];
+        }
+
+        startBlock = block.number;
+    }
+
+    /// Participant publishes their data and depending on the phase the data gets inserted
+    /// in the shares, responses or justifications mapping. Reverts if the participant
+    /// has already published their data for a phase or if the DKG has ended.
+    function publish(bytes calldata value) external onlyParticipant {
+        uint256 blocksSinceStart = block.number - startBlock;
+
+        if (blocksSinceStart <= PHASE_DURATION) {
+            require(
+                shares[msg.sender].length == 0,
+                "you have already published your shares"
+            );
+            shares[msg.sender] = value;
+        } else if (blocksSinceStart <= 2 * PHASE_DURATION) {
+            require(
+                responses[msg.sender].length == 0,
+                "you have already published your responses"
+            );
+            responses[msg.sender] = value;
+        } else if (blocksSinceStart <= 3 * PHASE_DURATION) {
+            require(
+                justifications[msg.sender].length == 0,
+                "you have already published your justifications"
+            );
+            justifications[msg.sender] = value;
+        } else {
+            revert("DKG has ended");
+        }
+    }
+
+    // Helpers to fetch data in the mappings. If a participant has not published their
+    // data for a phase, the array element at their index is expected to be 0
+
+    /// Gets the participants' shares
+    function getShares() external view returns (bytes[] memory) {
+        bytes[] memory _shares = new bytes[](participants.length);
+        for (uint256 i = 0; i < participants.length; i++) {
+            _shares[i] = shares[participants[i]];
+        }
+
+        return _shares;
+    }
+
+    /// Gets the participants' responses
+    function getResponses() external view returns (bytes[] memory) {
+        bytes[] memory _responses = new bytes[](participants.length);
+        for (uint256 i = 0; i < participants.length; i++) {
+            _responses[i] = responses[participants[i]];
+        }
+
+        return _responses;
+    }
+
+    /// Gets the participants' justifications
+    function getJustifications() external view returns (bytes[] memory) {
+        bytes[] memory _justifications = new bytes[](participants.length);
+        for (uint256 i = 0; i < participants.length; i++) {
+            _justifications[i] = justifications[participants[i]];
+        }
+
+        return _justifications;
+    }
+
+    /// Gets the participants' ethereum addresses
+    function getParticipants() external view returns (address[] memory) {
+        return participants;
+    }
+
+    /// Gets the participants' BLS keys along with the thershold of the DKG
+    function getBlsKeys() external view returns (uint256, bytes[] memory) {
+        bytes[] memory _keys = new bytes[](participants.length);
+        for (uint256 i = 0; i < participants.length; i++) {
+            _keys[i] = keys[participants[i]];
+        }
+
+        return (THRESHOLD, _keys);
+    }
+
+    /// Returns the current phase of the DKG.
+    function inPhase() public view returns (uint256) {
+        if (startBlock == 0) {
+            return 0;
+        }
+
+        uint256 blocksSinceStart = block.number - startBlock;
+
+        if (blocksSinceStart <= PHASE_DURATION) {
+            return 1;
+        }
+
+        if (blocksSinceStart <= 2 * PHASE_DURATION) {
+            return 2;
+        }
+
+        if (blocksSinceStart <= 3 * PHASE_DURATION) {
+            return 3;
+        }
+
+        revert("DKG Ended");
+    }
+}
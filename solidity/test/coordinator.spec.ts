@@ -0,0 +1,103 @@
+import { use, expect } from 'chai'
+import { ethers } from "ethers"
+
+import { deployContract, solidity } from 'ethereum-waffle'
+import { waffle } from "@nomiclabs/buidler";
+
+// The actual contract
+import Coordinator from "../build/Coordinator.json"
+
+use(solidity);
+
+
+describe('Coordinator', () => {
+    const provider = waffle.provider;
+    const [deployer, ...participants] = provider.getWallets();
+
+    const timeTravel = async (blocks: number) => {
+      for (let i = 0; i < blocks; i++) {
+        await provider.send('evm_mine', [])
+      }
+    }
+
+    const data = "0x2222222222222222222222222222222222222222222222222222222222222222"
+
+    const PHASE_DURATION = 30;
+    const THRESHOLD = 3;
+    const GROUP_INDEX = 1;
+    const GROUP_EPOCH = 2;
+
+    let coordinator: ethers.Contract;
+
+    beforeEach(async () => {
+        coordinator = await deployContract(deployer, Coordinator, [THRESHOLD, PHASE_DURATION, GROUP_INDEX, GROUP_EPOCH]);
+    })
+
+    describe('Initialization', async () => {
+        it('only owner can initialize', async () => {
+            coordinator = coordinator.connect(participants[0]);
+            await expect(coordinator.initialize([participants[0].address], ["0x1234"])).revertedWith("only owner may initialize the DKG")
+        })
+
+        it('cannot initialize twice', async () => {
+            await coordinator.initialize([participants[0].address], ["0x1234"])
+            await expect(coordinator.initialize([participants[1].address], ["0x4567"])).revertedWith("DKG has already started")
+        })
+
+        it('rejects mismatched nodes and keys', async () => {
+            await expect(coordinator.initialize([participants[0].address], [])).revertedWith("nodes and keys length mismatch")
+        })
+
+        it('rejects duplicate participants', async () => {
+            await expect(coordinator.initialize([participants[0].address, participants[0].address], ["0x1234", "0x4567"])).revertedWith("duplicate participant")
+        })
+
+        it('cannot publish if not a participant', async () => {
+            await coordinator.initialize([participants[0].address], ["0x1234"])
+            await expect(coordinator.connect(participants[1]).publish(data)).revertedWith("you are not a participant!");
+        })
+    })
+
+    it('e2e', async() => {
+        expect(await coordinator.GROUP_INDEX()).to.equal(GROUP_INDEX);
+        expect(await coordinator.GROUP_EPOCH()).to.equal(GROUP_EPOCH);
+        expect(await coordinator.inPhase()).to.equal(0);
+
+        await coordinator.initialize([participants[0].address, participants[1].address], ["0x1234", "0x4567"])
+
+        expect(await coordinator.inPhase()).to.equal(1);
+        expect(await coordinator.getParticipants()).to.deep.equal([participants[0].address, participants[1].address]);
+
+        const keys = await coordinator.getBlsKeys()
+        expect(keys[0]).to.equal(THRESHOLD)
+        expect(keys[1]).to.deep.equal(["0x1234", "0x4567"])
+
+        // only the second participant publishes their shares
+        const my_shares = "0x123456";
+        await coordinator.connect(participants[1]).publish(my_shares)
+
+        const shares = await coordinator.getShares()
+        expect(shares[0]).to.equal("0x")
+        expect(shares[1]).to.equal(my_shares)
+
+        await timeTravel(PHASE_DURATION)
+        expect(await coordinator.inPhase()).to.equal(2);
+
+        const my_responses = "0x234567";
+        await coordinator.connect(participants[1]).publish(my_responses)
+        await expect(coordinator.connect(participants[1]).publish(my_responses)).revertedWith("you have already published your responses")
+
+        await timeTravel(PHASE_DURATION)
+        expect(await coordinator.inPhase()).to.equal(3);
+
+        const my_justifications = "0x345678";
+        await coordinator.connect(participants[1]).publish(my_justifications)
+
+        const justifications = await coordinator.getJustifications()
+        expect(justifications[0]).to.equal("0x")
+        expect(justifications[1]).to.equal(my_justifications)
+
+        await timeTravel(PHASE_DURATION)
+        await expect(coordinator.inPhase()).revertedWith("VM Exception while processing transaction: revert DKG Ended")
+    })
+})